@@ -1,10 +1,12 @@
 #![no_std]
 
 /// A date and time represented with BCD numbers.
+#[derive(Copy, Clone, Debug)]
 pub struct DateTime {
     pub second : BCD,
     pub minute : BCD,
     pub hour: BCD,
+    /// The day of the week, from 0 for Monday to 6 for Sunday.
     pub day: u8,
     pub date: BCD,
     pub month: BCD,
@@ -54,7 +56,7 @@ impl BCD {
 
 impl core::convert::Into<u8> for BCD {
     fn into(self) -> u8 {
-        (self.0 >> 4) * 10 + self.0 & 0xf
+        (self.0 >> 4) * 10 + (self.0 & 0xf)
     }
 }
 
@@ -63,3 +65,45 @@ impl core::convert::From<u8> for BCD {
         Self(((v / 10) << 4) | (v % 10))
     }
 }
+
+/// Returns true if the given year has 29 days in February.
+pub fn is_leap_year(year: u16) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Returns the number of days in the given month of the given year, with
+/// months numbered from 1 for January.
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the day of the week for the given date, numbered as for
+/// `DateTime::day`. Months are numbered from 1 for January.
+pub fn weekday(year: u16, month: u8, date: u8) -> u8 {
+    // This is Sakamoto's method, which counts from Sunday.
+    const OFFSETS: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let y = if month < 3 { year - 1 } else { year };
+    let sunday_based =
+        (y + y / 4 - y / 100 + y / 400 + OFFSETS[(month as usize - 1) % 12] + date as u16) % 7;
+    ((sunday_based + 6) % 7) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcd_to_u8() {
+        for v in 0..100u8 {
+            let bcd = BCD::from(v);
+            assert_eq!(bcd.raw(), (v / 10) << 4 | (v % 10));
+            let back: u8 = bcd.into();
+            assert_eq!(back, v);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! Alarms, which sound the buzzer at a particular time on some days of the
+//! week.
+
+use crate::blockfont::BlockFont;
+use crate::minifont::MiniFont;
+use bcdtime::DateTime;
+use graphics::layout::{self, Align, Overflow};
+use graphics::vector::Rect;
+
+/// The number of separate alarms the user can set.
+pub const COUNT: usize = 3;
+
+/// The number of minutes a snoozed alarm waits before ringing again, unless
+/// the user chooses otherwise.
+pub const DEFAULT_SNOOZE_MINUTES: u8 = 9;
+
+/// The longest snooze the user can choose, in minutes.
+pub const MAX_SNOOZE_MINUTES: u8 = 30;
+
+/// The number of ticks an alarm rings for if nobody stops it, which is five
+/// minutes.
+pub const RING_TICKS: u16 = 600;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+const MINUTES_PER_WEEK: u16 = 7 * MINUTES_PER_DAY;
+
+/// The choices of which days an alarm rings on that the menu offers, with
+/// their names.
+pub const REPEATS: [(u8, &str); 10] = [
+    (Alarm::EVERY_DAY, "EVERY DAY"),
+    (Alarm::WEEKDAYS, "WEEKDAYS"),
+    (Alarm::WEEKENDS, "WEEKENDS"),
+    (1 << 0, "MONDAYS"),
+    (1 << 1, "TUESDAYS"),
+    (1 << 2, "WEDNESDAYS"),
+    (1 << 3, "THURSDAYS"),
+    (1 << 4, "FRIDAYS"),
+    (1 << 5, "SATURDAYS"),
+    (1 << 6, "SUNDAYS"),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    pub enabled: bool,
+    pub hour: u8,
+    pub minute: u8,
+    /// The days of the week the alarm rings on, with bit 0 for Monday
+    /// through to bit 6 for Sunday, matching `DateTime::day`.
+    pub days: u8,
+}
+
+impl Alarm {
+    pub const EVERY_DAY: u8 = 0b111_1111;
+    pub const WEEKDAYS: u8 = 0b001_1111;
+    pub const WEEKENDS: u8 = 0b110_0000;
+
+    pub fn on_day(&self, day: u8) -> bool {
+        day < 7 && self.days & (1 << day) != 0
+    }
+
+    /// Returns true if the alarm should ring during the minute of the
+    /// given time.
+    pub fn matches(&self, dt: &DateTime) -> bool {
+        let hour: u8 = dt.hour.into();
+        let minute: u8 = dt.minute.into();
+        self.enabled && self.on_day(dt.day) && self.hour == hour && self.minute == minute
+    }
+}
+
+impl core::default::Default for Alarm {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: 7,
+            minute: 0,
+            days: Alarm::WEEKDAYS,
+        }
+    }
+}
+
+/// The next time an alarm will ring, as a day of the week numbered as for
+/// `DateTime::day`, an hour and a minute.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NextAlarm {
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// Keeps track of whether an alarm is ringing or snoozing.
+pub struct AlarmState {
+    // The number of ticks since the alarm started ringing, if it is.
+    ringing: Option<u16>,
+    // The hour and minute at which a snoozed alarm will ring again.
+    snooze: Option<(u8, u8)>,
+    // The hour and minute when we last checked the alarms, so that each
+    // alarm rings only once even though we check on every tick.
+    checked: Option<(u8, u8)>,
+}
+
+impl AlarmState {
+    pub fn new() -> Self {
+        Self {
+            ringing: None,
+            snooze: None,
+            checked: None,
+        }
+    }
+
+    pub fn is_ringing(&self) -> bool {
+        self.ringing.is_some()
+    }
+
+    pub fn is_snoozing(&self) -> bool {
+        self.snooze.is_some()
+    }
+
+    /// Updates the state for a tick at the given time, returning true if an
+    /// alarm has just started ringing.
+    pub fn tick(&mut self, dt: &DateTime, alarms: &[Alarm]) -> bool {
+        if let Some(ticks) = self.ringing {
+            self.ringing = if ticks + 1 < RING_TICKS {
+                Some(ticks + 1)
+            } else {
+                None
+            };
+        }
+
+        let now = (dt.hour.into(), dt.minute.into());
+        if self.checked == Some(now) {
+            return false;
+        }
+        self.checked = Some(now);
+
+        let due = self.snooze == Some(now) || alarms.iter().any(|alarm| alarm.matches(dt));
+        if due && !self.is_ringing() {
+            self.snooze = None;
+            self.ringing = Some(0);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Silences the ringing alarm, and cancels any snooze.
+    pub fn stop(&mut self) {
+        self.ringing = None;
+        self.snooze = None;
+    }
+
+    /// Silences the ringing alarm until the given number of minutes after
+    /// the given time.
+    pub fn snooze(&mut self, dt: &DateTime, minutes: u8) {
+        let hour: u8 = dt.hour.into();
+        let minute: u8 = dt.minute.into();
+        let later = (hour as u16 * 60 + minute as u16 + minutes as u16) % MINUTES_PER_DAY;
+        self.ringing = None;
+        self.snooze = Some(((later / 60) as u8, (later % 60) as u8));
+    }
+
+    /// Returns when an alarm will next ring after the given time, if any
+    /// will.
+    pub fn next(&self, dt: &DateTime, alarms: &[Alarm]) -> Option<NextAlarm> {
+        let hour: u8 = dt.hour.into();
+        let minute: u8 = dt.minute.into();
+        let now = minute_of_week(dt.day, hour, minute);
+
+        // Measures how far after the current minute the given minute of the
+        // week is, counting the current minute as a whole week away.
+        let distance = |at: u16| (at + MINUTES_PER_WEEK - now - 1) % MINUTES_PER_WEEK;
+
+        let mut best: Option<u16> = None;
+        let mut consider = |at: u16| {
+            let closer = match best {
+                Some(best) => distance(at) < distance(best),
+                None => true,
+            };
+            if closer {
+                best = Some(at);
+            }
+        };
+        if let Some((hour, minute)) = self.snooze {
+            let today = minute_of_week(dt.day, hour, minute);
+            consider(today);
+            consider((today + MINUTES_PER_DAY) % MINUTES_PER_WEEK);
+        }
+        for alarm in alarms.iter().filter(|alarm| alarm.enabled) {
+            for day in (0..7).filter(|day| alarm.on_day(*day)) {
+                consider(minute_of_week(day, alarm.hour, alarm.minute));
+            }
+        }
+
+        best.map(|at| NextAlarm {
+            day: (at / MINUTES_PER_DAY) as u8,
+            hour: ((at % MINUTES_PER_DAY) / 60) as u8,
+            minute: (at % 60) as u8,
+        })
+    }
+}
+
+impl core::default::Default for AlarmState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the position of the given choice of days in `REPEATS`, or
+/// `None` if the menu doesn't offer that choice.
+pub fn repeat_index(days: u8) -> Option<usize> {
+    REPEATS.iter().position(|(mask, _)| *mask == days)
+}
+
+/// Draws the screen shown while an alarm is ringing. The word "ALARM" is
+/// shown only when `flash` is set, so that it flashes when drawn
+/// alternately with and without it.
+pub fn draw_ringing<D: graphics::Display>(
+    dt: &DateTime,
+    hr24: bool,
+    flash: bool,
+    disp: &mut D,
+) -> Result<(), D::Error> {
+    if flash {
+        let area = Rect::new4(0, 4, 256, 24);
+        layout::draw_text_in(
+            &BlockFont,
+            "ALARM",
+            disp,
+            area,
+            Align::Center,
+            Overflow::Clip,
+        )?;
+    }
+
+    let mut hour: u8 = dt.hour.into();
+    if !hr24 {
+        hour = (hour + 11) % 12 + 1;
+    }
+    let time = [
+        (b'0' + hour / 10) as char,
+        (b'0' + hour % 10) as char,
+        ':',
+        (b'0' + dt.minute.tens() as u8) as char,
+        (b'0' + dt.minute.units() as u8) as char,
+    ];
+    let area = Rect::new4(0, 28, 256, 48);
+    let chars = time.iter().cloned();
+    layout::draw_chars_in(&BlockFont, chars, disp, area, Align::Center, Overflow::Clip)?;
+
+    let area = Rect::new4(0, 55, 256, 60);
+    let hint = "SELECT TO STOP   UP OR DOWN TO SNOOZE";
+    layout::draw_text_in(&MiniFont, hint, disp, area, Align::Center, Overflow::Clip)?;
+    Ok(())
+}
+
+fn minute_of_week(day: u8, hour: u8, minute: u8) -> u16 {
+    (day as u16 % 7) * MINUTES_PER_DAY + hour as u16 * 60 + minute as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bcdtime::BCD;
+
+    // Returns a time on the given day of the week. The date doesn't matter
+    // to alarms.
+    fn at(day: u8, hour: u8, minute: u8) -> DateTime {
+        DateTime {
+            second: BCD::from(0),
+            minute: BCD::from(minute),
+            hour: BCD::from(hour),
+            day: day,
+            date: BCD::from(1),
+            month: BCD::from(1),
+            year: BCD::from(0),
+            hr24: true,
+        }
+    }
+
+    fn alarm(hour: u8, minute: u8, days: u8) -> Alarm {
+        Alarm {
+            enabled: true,
+            hour: hour,
+            minute: minute,
+            days: days,
+        }
+    }
+
+    fn next(day: u8, hour: u8, minute: u8) -> Option<NextAlarm> {
+        Some(NextAlarm {
+            day: day,
+            hour: hour,
+            minute: minute,
+        })
+    }
+
+    #[test]
+    fn rings_on_chosen_days() {
+        let alarms = [alarm(7, 0, Alarm::WEEKDAYS)];
+        let mut state = AlarmState::new();
+        assert!(!state.tick(&at(0, 6, 59), &alarms));
+        assert!(state.tick(&at(0, 7, 0), &alarms));
+        assert!(state.is_ringing());
+        // It rings only once, even though we tick twice a minute.
+        assert!(!state.tick(&at(0, 7, 0), &alarms));
+        state.stop();
+        assert!(!state.tick(&at(0, 7, 0), &alarms));
+        assert!(!state.is_ringing());
+
+        // Saturday isn't a weekday.
+        assert!(!state.tick(&at(5, 7, 0), &alarms));
+
+        // Nor does a disabled alarm ring.
+        let alarms = [Alarm {
+            enabled: false,
+            ..alarm(7, 0, Alarm::EVERY_DAY)
+        }];
+        assert!(!AlarmState::new().tick(&at(0, 7, 0), &alarms));
+    }
+
+    #[test]
+    fn stops_ringing_by_itself() {
+        let alarms = [alarm(7, 0, Alarm::EVERY_DAY)];
+        let mut state = AlarmState::new();
+        assert!(state.tick(&at(2, 7, 0), &alarms));
+        for i in 0..RING_TICKS {
+            assert!(state.is_ringing(), "stopped after {} ticks", i);
+            state.tick(&at(2, 7, 0), &alarms);
+        }
+        assert!(!state.is_ringing());
+    }
+
+    #[test]
+    fn snooze() {
+        let alarms = [alarm(7, 0, Alarm::EVERY_DAY)];
+        let mut state = AlarmState::new();
+        assert!(state.tick(&at(2, 7, 0), &alarms));
+        state.snooze(&at(2, 7, 0), 9);
+        assert!(!state.is_ringing());
+        assert!(state.is_snoozing());
+        assert_eq!(state.next(&at(2, 7, 0), &alarms), next(2, 7, 9));
+
+        assert!(!state.tick(&at(2, 7, 8), &alarms));
+        assert!(state.tick(&at(2, 7, 9), &alarms));
+        assert!(!state.is_snoozing());
+
+        // Stopping the alarm cancels a snooze.
+        state.snooze(&at(2, 7, 9), 9);
+        state.stop();
+        assert!(!state.is_snoozing());
+        assert!(!state.tick(&at(2, 7, 18), &alarms));
+    }
+
+    #[test]
+    fn snooze_past_midnight() {
+        let alarms = [alarm(23, 55, 1 << 2)];
+        let mut state = AlarmState::new();
+        assert!(state.tick(&at(2, 23, 55), &alarms));
+        state.snooze(&at(2, 23, 55), 10);
+        assert_eq!(state.next(&at(2, 23, 55), &alarms), next(3, 0, 5));
+        assert!(state.tick(&at(3, 0, 5), &alarms));
+    }
+
+    #[test]
+    fn next_alarm() {
+        let weekdays = alarm(7, 0, Alarm::WEEKDAYS);
+        let sundays = alarm(10, 30, 1 << 6);
+        let mut off = alarm(6, 0, Alarm::EVERY_DAY);
+        off.enabled = false;
+        let alarms = [weekdays, sundays, off];
+        let state = AlarmState::new();
+
+        // From Friday morning, after the alarm.
+        assert_eq!(state.next(&at(4, 8, 0), &alarms), next(6, 10, 30));
+        // From Sunday afternoon the week wraps around to Monday.
+        assert_eq!(state.next(&at(6, 12, 0), &alarms), next(0, 7, 0));
+        // The current minute counts as having passed.
+        assert_eq!(state.next(&at(1, 7, 0), &alarms), next(2, 7, 0));
+        assert_eq!(state.next(&at(1, 6, 59), &alarms), next(1, 7, 0));
+
+        assert_eq!(state.next(&at(1, 6, 59), &[off]), None);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::digitfont::{BigGlyph, DigitFont};
+use graphics::font::Font;
+use graphics::framebuffer::FrameBuffer;
+use graphics::vector::{Rect, Vector};
+use graphics::TILE_SIZE;
+
+/// The number of animation frames that a digit transition takes. Frames
+/// are advanced by `Event::Frame`.
+pub const FRAMES: u8 = 8;
+
+// The size of a big digit in pixels.
+const DIGIT_SIZE: Vector = Vector(8 * TILE_SIZE, 16 * TILE_SIZE);
+
+/// Selects how the big digits change from one value to the next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// The new digit replaces the old one immediately.
+    Cut,
+    /// Segments of the old digit shrink away towards their centres while
+    /// the segments of the new digit grow out from theirs. Segments that
+    /// both digits share stay put.
+    Morph,
+    /// The old digit scrolls up out of view as the new one scrolls in from
+    /// below.
+    Roll,
+    /// The new digit replaces the old one a few pixels at a time in a
+    /// dithered pattern, which is the closest we can get to a fade while
+    /// the displays only have two brightness levels.
+    Dissolve,
+}
+
+/// The state of one of the big digits, which may be part-way through a
+/// transition from an earlier value.
+pub struct DigitAnimation {
+    from: BigGlyph,
+    to: BigGlyph,
+    transition: Transition,
+    frame: u8,
+}
+
+impl DigitAnimation {
+    /// Creates an animation that is already showing the given glyph.
+    pub fn new(glyph: BigGlyph) -> Self {
+        Self {
+            from: glyph,
+            to: glyph,
+            transition: Transition::Cut,
+            frame: FRAMES,
+        }
+    }
+
+    /// Returns the glyph that the digit is showing, or will be showing once
+    /// any transition in progress has finished.
+    pub fn glyph(&self) -> BigGlyph {
+        self.to
+    }
+
+    /// Begins a transition to the given glyph if it is different from the
+    /// current one. If an earlier transition is still running then it is
+    /// abandoned and the new one starts from that earlier target.
+    pub fn set(&mut self, glyph: BigGlyph, transition: Transition) {
+        if glyph == self.to {
+            return;
+        }
+        self.from = self.to;
+        self.to = glyph;
+        self.transition = transition;
+        self.frame = match transition {
+            Transition::Cut => FRAMES,
+            _ => 0,
+        };
+    }
+
+    /// Returns true if a transition is in progress.
+    pub fn is_running(&self) -> bool {
+        self.frame < FRAMES
+    }
+
+    /// Advances any transition in progress by one frame, returning true
+    /// if the digit's appearance changed.
+    pub fn step(&mut self) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        self.frame += 1;
+        true
+    }
+
+    /// Draws the digit as it should currently appear, with its top-left
+    /// corner at the given position in tiles.
+    pub fn draw<D: graphics::Display>(
+        &self,
+        disp: &mut D,
+        top_left: Vector,
+    ) -> Result<(), D::Error> {
+        if !self.is_running() {
+            return DigitFont.draw_glyph(self.to, disp, top_left * TILE_SIZE);
+        }
+
+        // Transitions need to look at the pixels of both digits, but there
+        // isn't room on the stack for both of them at once, so we assemble
+        // the result one tile at a time from windows onto each digit that
+        // only hold the rows of tiles being looked at.
+        let mut from = Window::new(self.from);
+        let mut to = Window::new(self.to);
+        let digit_tiles = Rect::new(Vector(0, 0), DIGIT_SIZE).tiles();
+        for tile in digit_tiles {
+            let origin = tile * TILE_SIZE;
+            let mut bits = 0u16;
+            for p in Rect::new_sized(origin, Vector(TILE_SIZE, TILE_SIZE)).points() {
+                if self.pixel(&mut from, &mut to, p) {
+                    bits |= graphics::bitmap::tile_pixel_mask(p - origin);
+                }
+            }
+            if bits != 0 {
+                disp.draw_tile(bits, top_left + tile)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn pixel(&self, from: &mut Window, to: &mut Window, p: Vector) -> bool {
+        let frame = self.frame as i32;
+        let frames = FRAMES as i32;
+        match self.transition {
+            Transition::Cut => to.get_pixel(p),
+            Transition::Morph => match (from.get_pixel(p), to.get_pixel(p)) {
+                (true, true) => true,
+                (true, false) => within_segment(p, frames - frame),
+                (false, true) => within_segment(p, frame),
+                (false, false) => false,
+            },
+            Transition::Roll => {
+                let y = p.1 + DIGIT_SIZE.1 * frame / frames;
+                if y < DIGIT_SIZE.1 {
+                    from.get_pixel(Vector(p.0, y))
+                } else {
+                    to.get_pixel(Vector(p.0, y - DIGIT_SIZE.1))
+                }
+            }
+            Transition::Dissolve => {
+                let threshold = BAYER[(p.1 % 4) as usize][(p.0 % 4) as usize] as i32;
+                if threshold * frames < 16 * frame {
+                    to.get_pixel(p)
+                } else {
+                    from.get_pixel(p)
+                }
+            }
+        }
+    }
+}
+
+// The number of rows of tiles that a `Window` holds, which is enough for
+// any one row of tiles of a transition, and the size of those rows in
+// pixels.
+const WINDOW_ROWS: i32 = 2;
+const WINDOW_SIZE: Vector = Vector(DIGIT_SIZE.0, WINDOW_ROWS * TILE_SIZE);
+
+// A view of a few rows of tiles of a big digit, which draws whichever
+// rows are asked for as they are needed.
+struct Window {
+    glyph: BigGlyph,
+    // The first row of tiles held, measured from the top of the digit.
+    top: i32,
+    fb: FrameBuffer<[u8; 32]>,
+}
+
+impl Window {
+    fn new(glyph: BigGlyph) -> Self {
+        let mut ret = Self {
+            glyph: glyph,
+            top: 0,
+            fb: FrameBuffer::new(WINDOW_SIZE, [0u8; 32]),
+        };
+        ret.load(0);
+        ret
+    }
+
+    // Returns whether the given pixel of the digit is lit.
+    fn get_pixel(&mut self, p: Vector) -> bool {
+        let row = p.1.div_euclid(TILE_SIZE);
+        if row < self.top || row >= self.top + WINDOW_ROWS {
+            self.load(row);
+        }
+        self.fb.get_pixel(p - Vector(0, self.top * TILE_SIZE))
+    }
+
+    fn load(&mut self, top: i32) {
+        self.top = top;
+        self.fb = FrameBuffer::new(WINDOW_SIZE, [0u8; 32]);
+        // Tiles outside of the window are discarded, and drawing into a
+        // framebuffer cannot fail.
+        let pos = Vector(0, -top * TILE_SIZE);
+        DigitFont.draw_glyph(self.glyph, &mut self.fb, pos).unwrap();
+    }
+}
+
+// The thresholds for a 4x4 ordered dither, so that each step of a dissolve
+// changes pixels that are spread evenly across the digit.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// Decides whether the given pixel of a big digit lies within the central
+// part of its segment when that segment is scaled along its length to
+// `scale / FRAMES` of its full size.
+fn within_segment(p: Vector, scale: i32) -> bool {
+    // The horizontal segments occupy the top, middle and bottom two rows
+    // of tiles and run across the whole width of the digit. Everything
+    // else belongs to one of the vertical segments above or below the
+    // middle.
+    let (pos, start, end) = match p.1 / TILE_SIZE {
+        0..=1 | 7..=8 | 14..=15 => (p.0, 0, DIGIT_SIZE.0),
+        2..=6 => (p.1, 2 * TILE_SIZE, 7 * TILE_SIZE),
+        _ => (p.1, 9 * TILE_SIZE, 14 * TILE_SIZE),
+    };
+    // Working in half-pixels lets us measure from pixel centres without
+    // any fractions.
+    let dist = (2 * pos + 1 - (start + end)).abs();
+    dist * (FRAMES as i32) < (end - start) * scale
+}
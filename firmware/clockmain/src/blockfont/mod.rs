@@ -1,7 +1,27 @@
+use crate::locale::unaccented;
+use crate::tiles::Tile;
+use graphics::merge::MergeDisplay;
+use graphics::vector::Vector;
+use graphics::TILE_SIZE;
+
 static GLYPH_DATA: &[u8] = include_bytes!("blockfont.bin");
 static MAP_DATA: &[u8] = include_bytes!("blockfontmap.bin");
 
-fn map_ascii_code(ch: char) -> u8 {
+// The glyph to draw for the character 127, which stands in for characters
+// that the font doesn't have.
+const PLACEHOLDER: char = '\u{7f}';
+
+// Accented letters without a glyph of their own are drawn without their
+// accents. The capitals fill the whole height of the font, so there's no
+// room for theirs.
+fn map_char(ch: char) -> u8 {
+    glyph_index(ch)
+        .or_else(|| glyph_index(unaccented(ch)))
+        .or_else(|| glyph_index(PLACEHOLDER))
+        .unwrap_or(0)
+}
+
+fn glyph_index(ch: char) -> Option<u8> {
     let v = ch as usize;
     let mut start = 0;
     for i in (0..MAP_DATA.len()).step_by(2) {
@@ -9,14 +29,11 @@ fn map_ascii_code(ch: char) -> u8 {
         let max = MAP_DATA[i + 1] as usize;
 
         if v >= min && v <= max {
-            return (v + start - min) as u8;
+            return Some((v + start - min) as u8);
         }
         start += (max - min) + 1
     }
-
-    // If we fall out here then the character isn't in any of our ranges,
-    // so we'll return the placeholder character by default.
-    return map_ascii_code(127 as char);
+    None
 }
 
 #[derive(Debug)]
@@ -24,7 +41,7 @@ pub struct Glyph(&'static [u8]);
 
 impl Glyph {
     pub fn get(ch: char) -> Self {
-        let idx = map_ascii_code(ch);
+        let idx = map_char(ch);
         Self::get_idx(idx)
     }
 
@@ -38,6 +55,17 @@ impl Glyph {
         let shift = ((tx % 2) * 4) as usize;
         (self.0[byte_offset] >> shift) & 0xf
     }
+
+    /// Returns the width of the glyph in tiles, which is 6 for a few
+    /// special ligatures and 5 for everything else.
+    pub fn width(self) -> i32 {
+        for ty in 0..5 {
+            if self.get_tile_idx(5, ty) != 0 {
+                return 6;
+            }
+        }
+        5
+    }
 }
 
 impl Copy for Glyph {}
@@ -47,3 +75,98 @@ impl Clone for Glyph {
         Self(self.0)
     }
 }
+
+/// The block font, for use with the text functions in `graphics::font`.
+///
+/// Glyphs are five tiles tall and separated by a one-tile gap.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockFont;
+
+impl graphics::font::Font for BlockFont {
+    type Glyph = Glyph;
+
+    fn glyph(&self, ch: char) -> Glyph {
+        Glyph::get(ch)
+    }
+
+    fn advance(&self, glyph: Glyph) -> i32 {
+        (glyph.width() + 1) * TILE_SIZE
+    }
+
+    fn spacing(&self) -> i32 {
+        TILE_SIZE
+    }
+
+    fn line_height(&self) -> i32 {
+        5 * TILE_SIZE
+    }
+
+    fn draw_glyph<D: graphics::Display>(
+        &self,
+        glyph: Glyph,
+        disp: &mut D,
+        pos: Vector,
+    ) -> Result<(), D::Error> {
+        if pos.0 % TILE_SIZE == 0 && pos.1 % TILE_SIZE == 0 {
+            draw_glyph_tiles(glyph, disp, pos / TILE_SIZE)
+        } else {
+            draw_glyph_tiles(glyph, &mut MergeDisplay::new(disp, pos), Vector(0, 0))
+        }
+    }
+}
+
+fn draw_glyph_tiles<D: graphics::Display>(
+    glyph: Glyph,
+    disp: &mut D,
+    top_left: Vector,
+) -> Result<(), D::Error> {
+    for ty in 0..5 {
+        for tx in 0..6 {
+            let tile_idx = glyph.get_tile_idx(tx, ty);
+            if tile_idx != 0 {
+                let tile = Tile::get_by_index(tile_idx as usize);
+                disp.draw_tile(tile, Vector(tx + top_left.0, ty + top_left.1))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(a: char, b: char) -> bool {
+        Glyph::get(a).0 == Glyph::get(b).0
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(Glyph::get('A').width(), 5);
+        assert_eq!(Glyph::get('a').width(), 5);
+        assert_eq!(Glyph::get('\u{e9}').width(), 5);
+        assert_eq!(Glyph::get('\u{7c}').width(), 5); // st
+        assert_eq!(Glyph::get('\u{7b}').width(), 6); // th
+        assert_eq!(Glyph::get('\u{7d}').width(), 6); // nd
+    }
+
+    #[test]
+    fn accents() {
+        // The lowercase letters have their own accented glyphs.
+        assert!(!same('\u{e9}', 'e'));
+        assert!(!same('\u{f1}', 'n'));
+        assert!(!same('\u{df}', 's'));
+        // The capitals and a few rarer letters are drawn without them.
+        assert!(same('\u{c9}', 'E'));
+        assert!(same('\u{d1}', 'N'));
+        assert!(same('\u{e5}', 'a'));
+        assert!(same('\u{ff}', 'y'));
+    }
+
+    #[test]
+    fn placeholder() {
+        assert!(same('\u{d7}', PLACEHOLDER));
+        assert!(same('\u{20ac}', PLACEHOLDER));
+        assert!(!same('?', PLACEHOLDER));
+    }
+}
@@ -0,0 +1,85 @@
+//! Protection against burn-in on OLED panels, whose pixels fade unevenly
+//! when the same ones stay lit for long periods.
+//!
+//! The whole layout moves by a pixel every few minutes, and optionally at
+//! night the faces are replaced by a small time display that wanders
+//! around the screen.
+
+use crate::blockfont::BlockFont;
+use bcdtime::DateTime;
+use graphics::font::{self, Font};
+use graphics::vector::Vector;
+use graphics::TILE_SIZE;
+
+/// The number of minutes between each movement of the layout.
+pub const SHIFT_MINUTES: u16 = 5;
+
+// The offsets that the layout moves through in turn, in pixels, which
+// circle around its natural position.
+const SHIFTS: [Vector; 9] = [
+    Vector(0, 0),
+    Vector(1, 0),
+    Vector(1, 1),
+    Vector(0, 1),
+    Vector(-1, 1),
+    Vector(-1, 0),
+    Vector(-1, -1),
+    Vector(0, -1),
+    Vector(1, -1),
+];
+
+/// Returns the offset in pixels to draw the layout at for the given time.
+pub fn shift(dt: &DateTime) -> Vector {
+    let idx = minute_of_day(dt) / SHIFT_MINUTES;
+    SHIFTS[idx as usize % SHIFTS.len()]
+}
+
+/// Returns true if the given time is within the night-time hours, which
+/// start on the hour `start` and end on the hour `end`. The night may span
+/// midnight, and is empty if the two are equal.
+pub fn is_night(dt: &DateTime, start: u8, end: u8) -> bool {
+    let hour: u8 = dt.hour.into();
+    if start <= end {
+        hour >= start && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// Draws the screen saver, which shows just the time in the block font at
+/// a place on the display that changes every minute.
+pub fn draw_saver<D: graphics::Display>(
+    dt: &DateTime,
+    hr24: bool,
+    disp: &mut D,
+) -> Result<(), D::Error> {
+    let mut hour: u8 = dt.hour.into();
+    if !hr24 {
+        hour = (hour + 11) % 12 + 1;
+    }
+    let time = [
+        (b'0' + hour / 10) as char,
+        (b'0' + hour % 10) as char,
+        ':',
+        (b'0' + dt.minute.tens() as u8) as char,
+        (b'0' + dt.minute.units() as u8) as char,
+    ];
+    let chars = time.iter().cloned();
+
+    // We stick to whole tiles so that the text doesn't need merging, and
+    // scatter the positions so that consecutive minutes are far apart.
+    let size = disp.tile_bounds().size();
+    let width = font::measure_chars(&BlockFont, chars.clone()) / TILE_SIZE;
+    let height = BlockFont.line_height() / TILE_SIZE;
+    let room = Vector((size.0 - width).max(0) + 1, (size.1 - height).max(0) + 1);
+    let minute = minute_of_day(dt) as i32;
+    let pos = Vector((minute * 37) % room.0, (minute * 7) % room.1);
+    font::draw_chars(&BlockFont, chars, disp, pos * TILE_SIZE)?;
+    Ok(())
+}
+
+fn minute_of_day(dt: &DateTime) -> u16 {
+    let hour: u8 = dt.hour.into();
+    let minute: u8 = dt.minute.into();
+    hour as u16 * 60 + minute as u16
+}
@@ -1,3 +1,8 @@
+use graphics::bitmap::draw_bitmap;
+use graphics::framebuffer::FrameBuffer;
+use graphics::vector::Vector;
+use graphics::TILE_SIZE;
+
 static DIGIT_DATA: &[u8] = include_bytes!("digits.bin");
 
 #[inline(always)]
@@ -26,7 +31,7 @@ const DE: u16 = 1 << 11;
 const EF: u16 = 1 << 12;
 const FA: u16 = 1 << 13;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Digit(u16);
 
 impl Digit {
@@ -115,3 +120,79 @@ impl Digit {
 }
 
 impl Copy for Digit {}
+
+/// A glyph in the big digit font.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BigGlyph {
+    Digit(Digit),
+    Colon,
+    /// A blank space as wide as a digit.
+    Space,
+}
+
+/// The big digit font used for the time, for use with the text functions in
+/// `graphics::font`.
+///
+/// Digits are eight tiles wide and sixteen tiles tall, and the colon is two
+/// tiles wide. Glyphs are separated by a two-tile gap.
+#[derive(Copy, Clone, Debug)]
+pub struct DigitFont;
+
+impl graphics::font::Font for DigitFont {
+    type Glyph = BigGlyph;
+
+    fn glyph(&self, ch: char) -> BigGlyph {
+        match ch {
+            ':' => BigGlyph::Colon,
+            ' ' => BigGlyph::Space,
+            _ => BigGlyph::Digit(Digit::get_ascii(ch)),
+        }
+    }
+
+    fn advance(&self, glyph: BigGlyph) -> i32 {
+        match glyph {
+            BigGlyph::Colon => 4 * TILE_SIZE,
+            _ => 10 * TILE_SIZE,
+        }
+    }
+
+    fn spacing(&self) -> i32 {
+        2 * TILE_SIZE
+    }
+
+    fn line_height(&self) -> i32 {
+        16 * TILE_SIZE
+    }
+
+    fn draw_glyph<D: graphics::Display>(
+        &self,
+        glyph: BigGlyph,
+        disp: &mut D,
+        pos: Vector,
+    ) -> Result<(), D::Error> {
+        if pos.0 % TILE_SIZE == 0 && pos.1 % TILE_SIZE == 0 {
+            return draw_glyph_tiles(glyph, disp, pos / TILE_SIZE);
+        }
+
+        // The segments of a digit overlap in various places, so to draw it
+        // at a position that isn't tile-aligned we render it into a small
+        // pixel buffer first and then copy that to the display.
+        let mut buf = [0u8; 4 * 64];
+        let mut fb = FrameBuffer::new(Vector(32, 64), &mut buf[..]);
+        // Drawing into a framebuffer cannot fail.
+        draw_glyph_tiles(glyph, &mut fb, Vector(0, 0)).unwrap();
+        draw_bitmap(disp, &fb.as_bitmap(), pos)
+    }
+}
+
+fn draw_glyph_tiles<D: graphics::Display>(
+    glyph: BigGlyph,
+    disp: &mut D,
+    top_left: Vector,
+) -> Result<(), D::Error> {
+    match glyph {
+        BigGlyph::Digit(digit) => crate::gfx::draw_big_digit_raw(digit, disp, top_left),
+        BigGlyph::Colon => crate::gfx::draw_colon(disp, top_left),
+        BigGlyph::Space => Ok(()),
+    }
+}
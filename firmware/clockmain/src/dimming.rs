@@ -0,0 +1,97 @@
+//! Chooses the display brightness to suit the time of day or the light in
+//! the room.
+
+use crate::burnin;
+use crate::settings::{Settings, BRIGHTNESS_LEVELS};
+use crate::worldclock::{self, ZONES};
+use bcdtime::DateTime;
+use suntimes::SunTimes;
+
+/// The brightness used at night or in the dark, on the scale used by
+/// `graphics::Display::set_brightness`.
+pub const NIGHT_BRIGHTNESS: u8 = 16;
+
+/// The ways the display can dim itself below the brightness the user chose.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dimming {
+    /// Always uses the brightness the user chose.
+    Off,
+    /// Dims during the night-time hours in the settings.
+    Night,
+    /// Follows the readings from an ambient light sensor, delivered as
+    /// `Event::Light`.
+    Sensor,
+    /// Dims between sunset and sunrise in the home city.
+    Sun,
+}
+
+impl Dimming {
+    pub const ALL: [Dimming; 4] = [Dimming::Off, Dimming::Night, Dimming::Sensor, Dimming::Sun];
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(idx: u8) -> Option<Self> {
+        Self::ALL.get(idx as usize).cloned()
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Dimming::Off => "OFF",
+            Dimming::Night => "NIGHT",
+            Dimming::Sensor => "SENSOR",
+            Dimming::Sun => "SUNSET",
+        }
+    }
+}
+
+/// Returns the brightness to use at the given time, given the most recent
+/// ambient light reading if there has been one.
+pub fn brightness(settings: &Settings, dt: &DateTime, light: Option<u8>) -> u8 {
+    let full = full_brightness(settings);
+    let dim = full.min(NIGHT_BRIGHTNESS);
+    match settings.dimming {
+        Dimming::Off => full,
+        Dimming::Night => {
+            if burnin::is_night(dt, settings.night_start, settings.night_end) {
+                dim
+            } else {
+                full
+            }
+        }
+        // Until the first reading arrives we assume the room is bright.
+        Dimming::Sensor => match light {
+            Some(light) => dim + ((full - dim) as u16 * light as u16 / 255) as u8,
+            None => full,
+        },
+        Dimming::Sun => {
+            if is_dark(settings, dt) {
+                dim
+            } else {
+                full
+            }
+        }
+    }
+}
+
+// Returns true if the sun is down in the home city at the given time.
+fn is_dark(settings: &Settings, dt: &DateTime) -> bool {
+    let home = &ZONES[settings.home_zone as usize % ZONES.len()];
+    let hour: u8 = dt.hour.into();
+    let minute: u8 = dt.minute.into();
+    let now = hour as i16 * 60 + minute as i16;
+    match worldclock::sun_times(dt, home) {
+        // Far from the equator in summer the sun can set after midnight,
+        // in which case the dark part of the day is the middle.
+        SunTimes::Rises { sunrise, sunset } if sunrise <= sunset => now < sunrise || now >= sunset,
+        SunTimes::Rises { sunrise, sunset } => now >= sunset && now < sunrise,
+        SunTimes::AlwaysUp => false,
+        SunTimes::AlwaysDown => true,
+    }
+}
+
+/// Returns the brightness the user chose, without any dimming.
+pub fn full_brightness(settings: &Settings) -> u8 {
+    (settings.brightness as u16 * 255 / BRIGHTNESS_LEVELS as u16) as u8
+}
@@ -0,0 +1,157 @@
+//! The events that drive the app, and the queue that carries them from the
+//! platform's interrupt handlers to `App::update`.
+
+use crate::input::ButtonEvent;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Something that happened since the last update.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Sent every half-second, which toggles the colon and rereads the
+    /// time.
+    Tick,
+    /// Sent at a higher rate, of around 25 per second, to drive
+    /// animations.
+    Frame,
+    /// The user asked to switch to the next watch face.
+    NextFace,
+    /// The user pressed one of the clock's buttons.
+    Button(ButtonEvent),
+    /// A new reading from an ambient light sensor, from 0 for darkness up
+    /// to 255 for bright light.
+    Light(u8),
+    /// A new reading from a thermometer, in quarters of a degree Celsius.
+    Temperature(i16),
+}
+
+/// The number of events that an `EventQueue` can hold at once.
+pub const QUEUE_CAPACITY: usize = 15;
+
+// The ring buffer always keeps one slot empty, so that a full queue can be
+// told apart from an empty one.
+const SLOTS: usize = QUEUE_CAPACITY + 1;
+
+/// A fixed-size queue of events, which interrupt handlers can push into
+/// while the main loop is taking events out.
+///
+/// The queue doesn't disable interrupts, so it works even on cores that
+/// have no atomic read-modify-write instructions. In return, no push may
+/// interrupt another, so everything that pushes must run at the same
+/// interrupt priority or disable interrupts while it pushes, and only one
+/// place may pop.
+pub struct EventQueue {
+    events: UnsafeCell<[Event; SLOTS]>,
+    // The slot holding the oldest event, which only `pop` changes.
+    head: AtomicU8,
+    // The slot that the next event will go in, which only `push` changes.
+    tail: AtomicU8,
+}
+
+// The atomic indices ensure that each slot is only accessed by one side at
+// a time, given the restrictions described on the type.
+unsafe impl Sync for EventQueue {}
+
+impl EventQueue {
+    pub const fn new() -> Self {
+        Self {
+            events: UnsafeCell::new([Event::Tick; SLOTS]),
+            head: AtomicU8::new(0),
+            tail: AtomicU8::new(0),
+        }
+    }
+
+    /// Adds an event to the back of the queue, or returns it if the queue
+    /// is already full.
+    pub fn push(&self, evt: Event) -> Result<(), Event> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next = (tail + 1) % SLOTS as u8;
+        if next == self.head.load(Ordering::Acquire) {
+            return Err(evt);
+        }
+        // The consumer won't read this slot until we advance the tail.
+        unsafe {
+            (*self.events.get())[tail as usize] = evt;
+        }
+        self.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+
+    /// Removes the event at the front of the queue, if there is one.
+    pub fn pop(&self) -> Option<Event> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // The producer won't write this slot until we advance the head.
+        let evt = unsafe { (*self.events.get())[head as usize] };
+        self.head.store((head + 1) % SLOTS as u8, Ordering::Release);
+        Some(evt)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Relaxed) == self.tail.load(Ordering::Relaxed)
+    }
+}
+
+impl core::default::Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_empty() {
+        let queue = EventQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn full_at_capacity() {
+        let queue = EventQueue::new();
+        for i in 0..QUEUE_CAPACITY {
+            assert_eq!(queue.push(Event::Light(i as u8)), Ok(()));
+        }
+        assert_eq!(queue.push(Event::Tick), Err(Event::Tick));
+        for i in 0..QUEUE_CAPACITY {
+            assert_eq!(queue.pop(), Some(Event::Light(i as u8)));
+        }
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn wraps_around() {
+        let queue = EventQueue::new();
+        // Keeping a few events in the queue walks them all the way around
+        // the buffer a couple of times.
+        for i in 0..3u8 {
+            queue.push(Event::Light(i)).unwrap();
+        }
+        let end = (SLOTS * 2 + 5) as u8;
+        for i in 3..end {
+            queue.push(Event::Light(i)).unwrap();
+            assert_eq!(queue.pop(), Some(Event::Light(i - 3)));
+        }
+
+        // The queue still fills up at the same point when the free slots
+        // are split between the end of the buffer and the start.
+        let mut pushed = 0;
+        while queue.push(Event::Frame).is_ok() {
+            pushed += 1;
+        }
+        assert_eq!(pushed, QUEUE_CAPACITY - 3);
+        for i in end - 3..end {
+            assert_eq!(queue.pop(), Some(Event::Light(i)));
+        }
+        for _ in 0..pushed {
+            assert_eq!(queue.pop(), Some(Event::Frame));
+        }
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+use super::{BatteryPos, Face, FaceContext};
+use graphics::vector::Vector;
+
+/// Shows only the time, centered on the display.
+pub struct BigTime;
+
+impl Face for BigTime {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        // The time is 42 tiles wide, leaving 11 tiles on either side.
+        super::draw_time(ctx, disp, Vector(11, 0))?;
+        super::draw_battery(ctx, disp, BatteryPos::TopRight)
+    }
+}
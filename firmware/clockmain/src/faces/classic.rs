@@ -0,0 +1,24 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// The original face, showing the time along with the weekday and the date
+/// in the user's chosen format.
+pub struct Classic;
+
+impl Face for Classic {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        super::draw_time(ctx, disp, Vector(0, 0))?;
+        super::draw_battery(ctx, disp, BatteryPos::TopRight)?;
+
+        let locale = ctx.settings.language.locale();
+        let weekday = locale.weekday(ctx.datetime.day).chars();
+        let area = Rect::new(Vector(COLUMN.start.0, 20), COLUMN.end);
+        super::draw_block_in(weekday, disp, area, Align::Right)?;
+
+        // The big digits end a little before the column, so the date can
+        // use some of the gap to fit more in the block font.
+        let area = Rect::new(Vector(COLUMN.start.0 - 8, 44), COLUMN.end);
+        super::draw_date(ctx, disp, area, Align::Right)
+    }
+}
@@ -0,0 +1,34 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// Shows the countdown timer in minutes and seconds.
+pub struct CountdownFace;
+
+impl Face for CountdownFace {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        let countdown = ctx.countdown;
+
+        // Once the countdown expires it flashes in time with the colon.
+        if !countdown.is_expired() || ctx.colon {
+            let seconds = countdown.remaining_seconds();
+            let (minutes, seconds) = ((seconds / 60) as u8, (seconds % 60) as u8);
+            super::draw_pair(minutes, seconds, disp, Vector(0, 0))?;
+        }
+
+        let state = if countdown.is_expired() {
+            "TIME UP"
+        } else if countdown.is_running() {
+            "RUNNING"
+        } else if countdown.is_reset() {
+            "READY"
+        } else {
+            "PAUSED"
+        };
+        let area = Rect::new4(COLUMN.start.0, 4, COLUMN.end.0, 9);
+        super::draw_mini_in("TIMER".chars(), disp, area, Align::Right)?;
+        let area = Rect::new4(COLUMN.start.0, 12, COLUMN.end.0, 17);
+        super::draw_mini_in(state.chars(), disp, area, Align::Right)?;
+        super::draw_battery(ctx, disp, BatteryPos::BottomRight)
+    }
+}
@@ -0,0 +1,42 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use crate::minifont::MiniFont;
+use graphics::font;
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// Shows the time along with the weekday, the day of the month, the month
+/// and the year.
+pub struct FullDate;
+
+impl Face for FullDate {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        let dt = ctx.datetime;
+        let locale = ctx.settings.language.locale();
+        super::draw_time(ctx, disp, Vector(0, 0))?;
+        // There's no room for the battery at the top of this face, so it
+        // goes in the bottom corner beside the month and year instead.
+        super::draw_battery(ctx, disp, BatteryPos::BottomRight)?;
+
+        let weekday = locale.weekday(dt.day).chars();
+        let area = Rect::new(Vector(COLUMN.start.0, 4), COLUMN.end);
+        super::draw_block_in(weekday, disp, area, Align::Right)?;
+
+        let mut buf = ['0'; 2];
+        let date = super::bcd_chars(dt.date, true, &mut buf);
+        let suffix = (locale.ordinal_suffix)(dt.date);
+        let chars = date.iter().cloned().chain(suffix.chars());
+        let area = Rect::new(Vector(COLUMN.start.0, 28), COLUMN.end);
+        super::draw_block_in(chars, disp, area, Align::Right)?;
+
+        // The DS3231 only keeps a two-digit year, so we assume the 2000s.
+        let mut buf = ['0'; 2];
+        let year = super::bcd_chars(dt.year, false, &mut buf);
+        let chars = locale
+            .month(dt.month)
+            .chars()
+            .chain(" 20".chars())
+            .chain(year.iter().cloned());
+        font::draw_chars(&MiniFont, chars, disp, Vector(COLUMN.start.0, 55))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,598 @@
+//! Watch faces, each of which lays out the clock's information on the
+//! display in a different way.
+
+use crate::anim::DigitAnimation;
+use crate::blockfont::BlockFont;
+use crate::icons;
+use crate::locale::Locale;
+use crate::minifont::MiniFont;
+use crate::settings::Settings;
+use crate::timer::{Countdown, Stopwatch};
+use bcdtime::{DateTime, BCD};
+use graphics::bitmap::tile_pixel_mask;
+use graphics::font::{self, Font};
+use graphics::layout::{self, Align, Overflow};
+use graphics::vector::{Rect, Vector};
+use graphics::TILE_SIZE;
+
+mod bigtime;
+mod classic;
+mod countdown;
+mod fulldate;
+mod seconds;
+mod stopwatch;
+mod suntimes;
+mod temperature;
+mod worldclock;
+
+pub use bigtime::BigTime;
+pub use classic::Classic;
+pub use countdown::CountdownFace;
+pub use fulldate::FullDate;
+pub use seconds::Seconds;
+pub use stopwatch::StopwatchFace;
+pub use suntimes::SunTimesFace;
+pub use temperature::Temperature;
+pub use worldclock::WorldClock;
+
+/// Everything a face might want to show, gathered up by `App` on each
+/// redraw.
+pub struct FaceContext<'a> {
+    pub datetime: &'a DateTime,
+    /// Whether the colon between the hours and minutes is currently lit.
+    pub colon: bool,
+    pub battery: bool,
+    /// The most recent temperature reading in quarters of a degree
+    /// Celsius, if the platform has a thermometer.
+    pub temperature: Option<i16>,
+    /// The big digits of the time, which animate independently of how
+    /// any particular face positions them.
+    pub digits: &'a [DigitAnimation; 4],
+    pub countdown: &'a Countdown,
+    pub stopwatch: &'a Stopwatch,
+    pub settings: &'a Settings,
+}
+
+/// Implemented by watch faces.
+pub trait Face {
+    /// Draws the face onto a display that has already been cleared.
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error>;
+}
+
+/// The ways that faces with room for only a short line of date can write
+/// it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DateFormat {
+    /// Just the day of the month, with an ordinal suffix.
+    Ordinal,
+    /// Like "18 Oct 2026".
+    DayMonthYear,
+    /// Like "2026-10-18".
+    YearMonthDay,
+    /// Like "Oct 18", or "18 Okt" in languages that put the day first.
+    MonthDay,
+}
+
+impl DateFormat {
+    pub const ALL: [DateFormat; 4] = [
+        DateFormat::Ordinal,
+        DateFormat::DayMonthYear,
+        DateFormat::YearMonthDay,
+        DateFormat::MonthDay,
+    ];
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(idx: u8) -> Option<Self> {
+        Self::ALL.get(idx as usize).cloned()
+    }
+
+    /// Returns a pattern describing the format, to be shown in the block
+    /// font.
+    pub fn label(self) -> &'static str {
+        match self {
+            DateFormat::Ordinal => "1\u{7c}", // 1st
+            DateFormat::DayMonthYear => "D MON YYYY",
+            DateFormat::YearMonthDay => "YYYY-MM-DD",
+            DateFormat::MonthDay => "MON D",
+        }
+    }
+
+    /// Writes the given date in this format and the given locale into the
+    /// buffer, returning the part of the buffer that was written. The
+    /// English ordinal suffixes are ligatures that only the block font has.
+    pub fn format<'a>(self, dt: &DateTime, locale: &Locale, buf: &'a mut [char; 11]) -> &'a [char] {
+        let mut len = 0;
+        let mut day = ['0'; 2];
+        let day = bcd_chars(dt.date, true, &mut day).iter().cloned();
+        let month = locale.month(dt.month).chars();
+        match self {
+            DateFormat::Ordinal => {
+                let suffix = (locale.ordinal_suffix)(dt.date).chars();
+                push_chars(buf, &mut len, day.chain(suffix));
+            }
+            DateFormat::DayMonthYear => {
+                push_chars(buf, &mut len, day.chain(" ".chars()).chain(month));
+                push_chars(buf, &mut len, " ".chars());
+                push_year(buf, &mut len, dt.year);
+            }
+            DateFormat::YearMonthDay => {
+                let (mut month_buf, mut date_buf) = (['0'; 2], ['0'; 2]);
+                let month = bcd_chars(dt.month, false, &mut month_buf).iter().cloned();
+                let date = bcd_chars(dt.date, false, &mut date_buf).iter().cloned();
+                push_year(buf, &mut len, dt.year);
+                push_chars(buf, &mut len, "-".chars().chain(month));
+                push_chars(buf, &mut len, "-".chars().chain(date));
+            }
+            DateFormat::MonthDay if locale.day_first => {
+                push_chars(buf, &mut len, day.chain(" ".chars()).chain(month));
+            }
+            DateFormat::MonthDay => {
+                push_chars(buf, &mut len, month.chain(" ".chars()).chain(day));
+            }
+        }
+        &buf[..len]
+    }
+}
+
+/// Whether, and how, faces that show the time also show the seconds.
+/// Faces that already show the seconds some other way ignore this.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SecondsStyle {
+    Off,
+    /// Small digits beside the hours and minutes.
+    Digits,
+    /// A bar along the bottom of the display that grows over the minute,
+    /// which takes the bottom row of tiles away from the face.
+    Bar,
+}
+
+impl SecondsStyle {
+    pub const ALL: [SecondsStyle; 3] = [SecondsStyle::Off, SecondsStyle::Digits, SecondsStyle::Bar];
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(idx: u8) -> Option<Self> {
+        Self::ALL.get(idx as usize).cloned()
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SecondsStyle::Off => "OFF",
+            SecondsStyle::Digits => "DIGITS",
+            SecondsStyle::Bar => "BAR",
+        }
+    }
+}
+
+/// Identifies one of the built-in faces, so that the user's choice can be
+/// stored and changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FaceId {
+    Classic,
+    BigTime,
+    Seconds,
+    FullDate,
+    Temperature,
+    Countdown,
+    Stopwatch,
+    WorldClock,
+    SunTimes,
+}
+
+impl FaceId {
+    /// All of the built-in faces, in the order that `next` cycles through
+    /// them.
+    pub const ALL: [FaceId; 9] = [
+        FaceId::Classic,
+        FaceId::BigTime,
+        FaceId::Seconds,
+        FaceId::FullDate,
+        FaceId::Temperature,
+        FaceId::Countdown,
+        FaceId::Stopwatch,
+        FaceId::WorldClock,
+        FaceId::SunTimes,
+    ];
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(idx: u8) -> Option<Self> {
+        Self::ALL.get(idx as usize).cloned()
+    }
+
+    /// Returns the face after this one, wrapping around after the last.
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() as usize + 1) % Self::ALL.len()]
+    }
+
+    /// Returns the face before this one, wrapping around before the first.
+    pub fn prev(self) -> Self {
+        Self::ALL[(self.index() as usize + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn draw<D: graphics::Display>(
+        self,
+        ctx: &FaceContext,
+        disp: &mut D,
+    ) -> Result<(), D::Error> {
+        if ctx.settings.seconds == SecondsStyle::Bar && self.follows_seconds_style() {
+            // The big digits fill the whole height of the display, so the
+            // bar shares tiles with their bottom edge. We draw it first and
+            // then add it to each tile the face draws, so that neither
+            // erases the other.
+            let bar = seconds_bar(ctx, disp.size());
+            graphics::shapes::fill_rect(disp, bar)?;
+            let mut disp = UnderBar {
+                inner: disp,
+                bar: bar,
+            };
+            return self.draw_face(ctx, &mut disp);
+        }
+        self.draw_face(ctx, disp)
+    }
+
+    // Whether the face shows the time of day without the seconds, and so
+    // adds them in the user's chosen style.
+    fn follows_seconds_style(self) -> bool {
+        ![FaceId::Seconds, FaceId::Countdown, FaceId::Stopwatch].contains(&self)
+    }
+
+    fn draw_face<D: graphics::Display>(
+        self,
+        ctx: &FaceContext,
+        disp: &mut D,
+    ) -> Result<(), D::Error> {
+        match self {
+            FaceId::Classic => Classic.draw(ctx, disp),
+            FaceId::BigTime => BigTime.draw(ctx, disp),
+            FaceId::Seconds => Seconds.draw(ctx, disp),
+            FaceId::FullDate => FullDate.draw(ctx, disp),
+            FaceId::Temperature => Temperature.draw(ctx, disp),
+            FaceId::Countdown => CountdownFace.draw(ctx, disp),
+            FaceId::Stopwatch => StopwatchFace.draw(ctx, disp),
+            FaceId::WorldClock => WorldClock.draw(ctx, disp),
+            FaceId::SunTimes => SunTimesFace.draw(ctx, disp),
+        }
+    }
+}
+
+// The area at the right of the display beside the big digits, in pixels,
+// where most faces put their smaller text.
+const COLUMN: Rect = Rect {
+    start: Vector(188, 0),
+    end: Vector(256, 64),
+};
+
+// The positions of the four big digits relative to the first, and of the
+// colon between them, in tiles.
+const DIGIT_POS: [Vector; 4] = [Vector(0, 0), Vector(10, 0), Vector(24, 0), Vector(34, 0)];
+const COLON_POS: Vector = Vector(20, 0);
+
+// The position of the small seconds digits relative to the first big
+// digit, in pixels, which puts them level with the top of the big digits
+// just after the last one.
+const SMALL_SECONDS_POS: Vector = Vector(174, 0);
+
+// The height of the seconds bar, in pixels. It runs along the bottom edge
+// of the big digits, so it's kept thin to leave their shape clear.
+const SECONDS_BAR_HEIGHT: i32 = 1;
+
+// Draws the big digits and colon with the top-left corner of the first
+// digit at the given position in tiles, followed by the seconds in small
+// digits if the user has chosen them.
+fn draw_time<D: graphics::Display>(
+    ctx: &FaceContext,
+    disp: &mut D,
+    top_left: Vector,
+) -> Result<(), D::Error> {
+    draw_hours_minutes(ctx, disp, top_left)?;
+    if ctx.settings.seconds == SecondsStyle::Digits {
+        let mut buf = ['0'; 2];
+        let seconds = bcd_chars(ctx.datetime.second, false, &mut buf);
+        let pos = top_left * TILE_SIZE + SMALL_SECONDS_POS;
+        font::draw_chars(&MiniFont, seconds.iter().cloned(), disp, pos)?;
+    }
+    Ok(())
+}
+
+// Draws the big digits and colon like `draw_time`, but never the seconds.
+fn draw_hours_minutes<D: graphics::Display>(
+    ctx: &FaceContext,
+    disp: &mut D,
+    top_left: Vector,
+) -> Result<(), D::Error> {
+    for (digit, pos) in ctx.digits.iter().zip(DIGIT_POS.iter()) {
+        digit.draw(disp, top_left + *pos)?;
+    }
+    if ctx.colon {
+        crate::gfx::draw_colon(disp, top_left + COLON_POS)?;
+    }
+    Ok(())
+}
+
+// Returns the area of a bar along the bottom of a display of the given
+// size whose length shows how far through the minute we are, in sixty
+// steps.
+fn seconds_bar(ctx: &FaceContext, size: Vector) -> Rect {
+    let second: u8 = ctx.datetime.second.into();
+    let width = size.0 * second.min(59) as i32 / 60;
+    Rect::new4(0, size.1 - SECONDS_BAR_HEIGHT, width, size.1)
+}
+
+// Wraps the display while a face draws over the seconds bar, adding the
+// part of the bar in each tile to the tile, since drawing it would
+// otherwise erase that part.
+struct UnderBar<'a, D: graphics::Display> {
+    inner: &'a mut D,
+    bar: Rect,
+}
+
+impl<'a, D: graphics::Display> graphics::Display for UnderBar<'a, D> {
+    type Error = D::Error;
+    type P = D::P;
+    const OFF: D::P = D::OFF;
+    const ON: D::P = D::ON;
+
+    fn size(&self) -> Vector {
+        self.inner.size()
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        self.inner.flip()
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.inner.clear()
+    }
+
+    fn draw_tile<T: graphics::Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        let area = Rect::new_sized(pos * TILE_SIZE, Vector(TILE_SIZE, TILE_SIZE));
+        let mut bits = tile.raw_pixel_data();
+        if let Some(bar) = area.intersection(self.bar) {
+            for p in bar.points() {
+                bits |= tile_pixel_mask(p - area.start);
+            }
+        }
+        self.inner.draw_tile(bits, pos)
+    }
+}
+
+// Draws a pair of two-digit numbers in the big digits, separated by a
+// colon, in the same positions that `draw_time` uses.
+fn draw_pair<D: graphics::Display>(
+    left: u8,
+    right: u8,
+    disp: &mut D,
+    top_left: Vector,
+) -> Result<(), D::Error> {
+    let digits = [left / 10 % 10, left % 10, right / 10 % 10, right % 10];
+    for (digit, pos) in digits.iter().zip(DIGIT_POS.iter()) {
+        crate::gfx::draw_big_digit(*digit, disp, top_left + *pos)?;
+    }
+    crate::gfx::draw_colon(disp, top_left + COLON_POS)
+}
+
+// Draws a line of mini font text aligned within the given rectangle.
+fn draw_mini_in<D, I>(chars: I, disp: &mut D, rect: Rect, align: Align) -> Result<(), D::Error>
+where
+    D: graphics::Display,
+    I: IntoIterator<Item = char>,
+    I::IntoIter: Clone,
+{
+    layout::draw_chars_in(&MiniFont, chars, disp, rect, align, Overflow::Clip)?;
+    Ok(())
+}
+
+// The corners of the display where faces can put the battery icon.
+#[derive(Copy, Clone)]
+enum BatteryPos {
+    TopRight,
+    BottomRight,
+}
+
+// The gap between the battery icon and the top edge of the display, which
+// keeps it level with the top of the weekday on faces that show one.
+const BATTERY_TOP_MARGIN: i32 = 4;
+
+// Draws the battery icon, if needed, in the given corner of the display.
+fn draw_battery<D: graphics::Display>(
+    ctx: &FaceContext,
+    disp: &mut D,
+    corner: BatteryPos,
+) -> Result<(), D::Error> {
+    if !ctx.battery {
+        return Ok(());
+    }
+    let size = disp.size();
+    let icon = &icons::BATTERY;
+    let y = match corner {
+        BatteryPos::TopRight => BATTERY_TOP_MARGIN,
+        BatteryPos::BottomRight => size.1 - icon.height(),
+    };
+    graphics::bitmap::draw_bitmap(disp, icon, Vector(size.0 - icon.width(), y))
+}
+
+// Draws the date in the user's chosen format aligned within the given
+// rectangle, which is as tall as a line of the block font. Dates too wide
+// for the block font use the mini font instead, centred vertically.
+fn draw_date<D: graphics::Display>(
+    ctx: &FaceContext,
+    disp: &mut D,
+    rect: Rect,
+    align: Align,
+) -> Result<(), D::Error> {
+    let mut buf = [' '; 11];
+    let locale = ctx.settings.language.locale();
+    let format = ctx.settings.date_format;
+    let date = format.format(ctx.datetime, locale, &mut buf);
+    let chars = date.iter().cloned();
+    if font::measure_chars(&BlockFont, chars.clone()) <= rect.width() {
+        return draw_block_in(chars, disp, rect, align);
+    }
+    let top = rect.start.1 + (BlockFont.line_height() - MiniFont.line_height()) / 2;
+    let rect = Rect::new4(rect.start.0, top, rect.end.0, rect.end.1);
+    draw_mini_in(chars, disp, rect, align)
+}
+
+// Draws a line of block font text aligned within the given rectangle.
+fn draw_block_in<D, I>(chars: I, disp: &mut D, rect: Rect, align: Align) -> Result<(), D::Error>
+where
+    D: graphics::Display,
+    I: IntoIterator<Item = char>,
+    I::IntoIter: Clone,
+{
+    layout::draw_chars_in(&BlockFont, chars, disp, rect, align, Overflow::Clip)?;
+    Ok(())
+}
+
+// Appends characters to a buffer of the given length, dropping any that
+// don't fit.
+fn push_chars<I: IntoIterator<Item = char>>(buf: &mut [char], len: &mut usize, chars: I) {
+    for ch in chars {
+        if let Some(slot) = buf.get_mut(*len) {
+            *slot = ch;
+            *len += 1;
+        }
+    }
+}
+
+// Appends the four digits of the given year to a buffer like `push_chars`.
+// The DS3231 only keeps a two-digit year, so we assume the 2000s.
+fn push_year(buf: &mut [char], len: &mut usize, year: BCD) {
+    let mut digits = ['0'; 2];
+    let year = bcd_chars(year, false, &mut digits);
+    push_chars(buf, len, "20".chars().chain(year.iter().cloned()));
+}
+
+// Returns the characters of a two-digit BCD number, which are both digits
+// unless `blank_zero` is set and the tens digit is zero, in which case
+// only the units digit is returned.
+fn bcd_chars(v: BCD, blank_zero: bool, buf: &mut [char; 2]) -> &[char] {
+    buf[0] = digit_char(v.tens());
+    buf[1] = digit_char(v.units());
+    if blank_zero && v.tens() == 0 {
+        &buf[1..]
+    } else {
+        &buf[..]
+    }
+}
+
+fn digit_char(v: i32) -> char {
+    (b'0' + v as u8) as char
+}
+
+// Writes the given time into the buffer and returns the number of
+// characters written. A 12-hour time has no leading zero, but is followed
+// by "A" or "P" instead.
+fn format_time(hour: u8, minute: u8, hr24: bool, buf: &mut [char; 6]) -> usize {
+    let mut len = 0;
+    let shown = if hr24 { hour } else { (hour + 11) % 12 + 1 };
+    if hr24 || shown >= 10 {
+        buf[len] = digit_char((shown / 10) as i32);
+        len += 1;
+    }
+    buf[len] = digit_char((shown % 10) as i32);
+    buf[len + 1] = ':';
+    buf[len + 2] = digit_char((minute / 10) as i32);
+    buf[len + 3] = digit_char((minute % 10) as i32);
+    len += 4;
+    if !hr24 {
+        buf[len] = if hour < 12 { 'A' } else { 'P' };
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Language;
+
+    // Checks the date in the given month of 2026 in the given format and
+    // language.
+    fn check(format: DateFormat, language: Language, date: u8, month: u8, want: &str) {
+        let dt = DateTime {
+            second: BCD::from(0),
+            minute: BCD::from(0),
+            hour: BCD::from(12),
+            day: 0,
+            date: BCD::from(date),
+            month: BCD::from(month),
+            year: BCD::from(26),
+            hr24: true,
+        };
+        let mut buf = [' '; 11];
+        let got = format.format(&dt, language.locale(), &mut buf);
+        assert!(
+            got.iter().cloned().eq(want.chars()),
+            "{:?} in {:?} gave {:?}, not {:?}",
+            format,
+            language,
+            got,
+            want
+        );
+    }
+
+    #[test]
+    fn english_ordinals() {
+        let check = |date, want| check(DateFormat::Ordinal, Language::English, date, 10, want);
+        check(1, "1\u{7c}");
+        check(2, "2\u{7d}");
+        check(3, "3\u{7e}");
+        check(4, "4\u{7b}");
+        // The teens are all "th".
+        check(11, "11\u{7b}");
+        check(12, "12\u{7b}");
+        check(13, "13\u{7b}");
+        check(21, "21\u{7c}");
+        check(22, "22\u{7d}");
+        check(23, "23\u{7e}");
+        check(31, "31\u{7c}");
+    }
+
+    #[test]
+    fn other_ordinals() {
+        check(DateFormat::Ordinal, Language::German, 18, 10, "18.");
+        check(DateFormat::Ordinal, Language::French, 1, 10, "1er");
+        check(DateFormat::Ordinal, Language::French, 2, 10, "2");
+        check(DateFormat::Ordinal, Language::French, 21, 10, "21");
+        check(DateFormat::Ordinal, Language::Spanish, 1, 10, "1");
+    }
+
+    #[test]
+    fn day_month_year() {
+        let format = DateFormat::DayMonthYear;
+        check(format, Language::English, 18, 10, "18 Oct 2026");
+        check(format, Language::English, 5, 1, "5 Jan 2026");
+        check(format, Language::German, 18, 10, "18 Okt 2026");
+        check(format, Language::German, 3, 3, "3 M\u{e4}r 2026");
+        check(format, Language::French, 14, 8, "14 Ao\u{fb} 2026");
+        check(format, Language::Spanish, 6, 1, "6 Ene 2026");
+    }
+
+    #[test]
+    fn year_month_day() {
+        // This is the same in every language, and always has both digits.
+        for &language in Language::ALL.iter() {
+            check(DateFormat::YearMonthDay, language, 5, 3, "2026-03-05");
+            check(DateFormat::YearMonthDay, language, 18, 10, "2026-10-18");
+        }
+    }
+
+    #[test]
+    fn month_day() {
+        let format = DateFormat::MonthDay;
+        check(format, Language::English, 18, 10, "Oct 18");
+        check(format, Language::English, 5, 12, "Dec 5");
+        check(format, Language::German, 18, 10, "18 Okt");
+        check(format, Language::French, 5, 2, "5 F\u{e9}v");
+        check(format, Language::Spanish, 24, 12, "24 Dic");
+    }
+}
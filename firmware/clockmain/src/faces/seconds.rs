@@ -0,0 +1,19 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// Shows the time with the seconds beside it.
+pub struct Seconds;
+
+impl Face for Seconds {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        super::draw_hours_minutes(ctx, disp, Vector(0, 0))?;
+        super::draw_battery(ctx, disp, BatteryPos::TopRight)?;
+
+        // The seconds sit level with the bottom of the digits.
+        let mut buf = ['0'; 2];
+        let seconds = super::bcd_chars(ctx.datetime.second, false, &mut buf);
+        let area = Rect::new(Vector(COLUMN.start.0, 44), COLUMN.end);
+        super::draw_block_in(seconds.iter().cloned(), disp, area, Align::Right)
+    }
+}
@@ -0,0 +1,88 @@
+use super::{Face, FaceContext, COLUMN};
+use crate::timer;
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// Shows the stopwatch in minutes and seconds, with tenths of a second
+/// beside them. Just after a lap is recorded, this shows the time of that
+/// lap instead of the total.
+pub struct StopwatchFace;
+
+impl Face for StopwatchFace {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        let stopwatch = ctx.stopwatch;
+
+        let mut label = ['L', 'A', 'P', ' ', ' ', ' ', ' '];
+        let (frames, label) = match stopwatch.lap(0) {
+            Some(frames) if stopwatch.is_showing_lap() => {
+                let len = 4 + format_number(stopwatch.lap_count(), &mut label[4..]);
+                (frames, &label[..len])
+            }
+            _ => (stopwatch.elapsed(), &label[..0]),
+        };
+        let (minutes, seconds, tenths) = displayed_time(frames);
+        super::draw_pair(minutes, seconds, disp, Vector(0, 0))?;
+
+        let area = Rect::new4(COLUMN.start.0, 4, COLUMN.end.0, 9);
+        if label.is_empty() {
+            super::draw_mini_in("STOPWATCH".chars(), disp, area, Align::Right)?;
+        } else {
+            super::draw_mini_in(label.iter().cloned(), disp, area, Align::Right)?;
+        }
+        if !stopwatch.is_running() {
+            let area = Rect::new4(COLUMN.start.0, 12, COLUMN.end.0, 17);
+            super::draw_mini_in("STOPPED".chars(), disp, area, Align::Right)?;
+        }
+
+        // The tenths sit level with the bottom of the digits.
+        let tenths = ['.', super::digit_char(tenths as i32)];
+        let area = Rect::new(Vector(COLUMN.start.0, 44), COLUMN.end);
+        super::draw_block_in(tenths.iter().cloned(), disp, area, Align::Right)
+    }
+}
+
+// Splits the given number of frames into the minutes, seconds and tenths
+// of a second to show. There are only two digits for the minutes, so times
+// from 100 minutes on stay at the longest time that fits.
+fn displayed_time(frames: u32) -> (u8, u8, u8) {
+    match timer::split_frames(frames) {
+        (minutes, seconds, tenths) if minutes < 100 => (minutes as u8, seconds, tenths),
+        _ => (99, 59, 9),
+    }
+}
+
+// Writes the decimal digits of the given number into the buffer, returning
+// how many were written. Numbers too big for the buffer lose their leading
+// digits.
+fn format_number(mut v: usize, buf: &mut [char]) -> usize {
+    let mut digits = [0u8; 3];
+    let mut len = 0;
+    while len < digits.len() && len < buf.len() {
+        digits[len] = (v % 10) as u8;
+        v /= 10;
+        len += 1;
+        if v == 0 {
+            break;
+        }
+    }
+    for i in 0..len {
+        buf[i] = super::digit_char(digits[len - 1 - i] as i32);
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::FRAMES_PER_SECOND;
+
+    #[test]
+    fn longest_time() {
+        let fps = FRAMES_PER_SECOND;
+        assert_eq!(displayed_time(0), (0, 0, 0));
+        assert_eq!(displayed_time(fps * 6000 - 1), (99, 59, 9));
+        assert_eq!(displayed_time(fps * 6000), (99, 59, 9));
+        assert_eq!(displayed_time(fps * 6061), (99, 59, 9));
+        assert_eq!(displayed_time(u32::MAX), (99, 59, 9));
+    }
+}
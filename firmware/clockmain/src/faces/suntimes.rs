@@ -0,0 +1,41 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use crate::worldclock::{self, ZONES};
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+use suntimes::SunTimes;
+
+/// Shows the time along with when the sun rises and sets in the home city.
+pub struct SunTimesFace;
+
+impl Face for SunTimesFace {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        super::draw_time(ctx, disp, Vector(0, 0))?;
+
+        let settings = ctx.settings;
+        let home = &ZONES[settings.home_zone as usize % ZONES.len()];
+        let line = |i: i32| Rect::new4(COLUMN.start.0 + 4, 4 + i * 10, COLUMN.end.0, 9 + i * 10);
+        super::draw_mini_in(home.label.chars(), disp, line(0), Align::Left)?;
+
+        match worldclock::sun_times(ctx.datetime, home) {
+            SunTimes::Rises { sunrise, sunset } => {
+                let times = [("RISE", sunrise), ("SET", sunset)];
+                for (i, (label, minutes)) in times.iter().enumerate() {
+                    let area = line(i as i32 + 1);
+                    super::draw_mini_in(label.chars(), disp, area, Align::Left)?;
+                    let (hour, minute) = ((minutes / 60) as u8, (minutes % 60) as u8);
+                    let mut buf = [' '; 6];
+                    let len = super::format_time(hour, minute, settings.hr24, &mut buf);
+                    super::draw_mini_in(buf[..len].iter().cloned(), disp, area, Align::Right)?;
+                }
+            }
+            // Near the poles the sun may not rise or set at all.
+            SunTimes::AlwaysUp => {
+                super::draw_mini_in("NO SUNSET".chars(), disp, line(1), Align::Left)?;
+            }
+            SunTimes::AlwaysDown => {
+                super::draw_mini_in("NO SUNRISE".chars(), disp, line(1), Align::Left)?;
+            }
+        }
+        super::draw_battery(ctx, disp, BatteryPos::BottomRight)
+    }
+}
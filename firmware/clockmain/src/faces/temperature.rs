@@ -0,0 +1,46 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// Shows the time along with the temperature in whole degrees Celsius.
+pub struct Temperature;
+
+impl Face for Temperature {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        super::draw_time(ctx, disp, Vector(0, 0))?;
+        super::draw_battery(ctx, disp, BatteryPos::TopRight)?;
+
+        let mut buf = ['-'; 4];
+        let len = match ctx.temperature {
+            Some(quarters) => format_degrees(quarters, &mut buf),
+            None => 2,
+        };
+        buf[len] = 'C';
+        let area = Rect::new(Vector(COLUMN.start.0, 44), COLUMN.end);
+        super::draw_block_in(buf[..=len].iter().cloned(), disp, area, Align::Right)
+    }
+}
+
+// Writes the given temperature, measured in quarter degrees, into the
+// buffer as a whole number of degrees and returns the number of characters
+// written. The buffer has room for a sign and two digits, which covers the
+// range of temperatures that the DS3231 can measure.
+fn format_degrees(quarters: i16, buf: &mut [char; 4]) -> usize {
+    let rounded = if quarters < 0 {
+        (quarters - 2) / 4
+    } else {
+        (quarters + 2) / 4
+    };
+    let mut len = 0;
+    if rounded < 0 {
+        buf[len] = '-';
+        len += 1;
+    }
+    let v = rounded.abs().min(99) as i32;
+    if v >= 10 {
+        buf[len] = super::digit_char(v / 10);
+        len += 1;
+    }
+    buf[len] = super::digit_char(v % 10);
+    len + 1
+}
@@ -0,0 +1,38 @@
+use super::{BatteryPos, Face, FaceContext, COLUMN};
+use crate::worldclock::{self, ZONES};
+use graphics::layout::Align;
+use graphics::vector::{Rect, Vector};
+
+/// Shows the time along with the times in up to three other cities.
+pub struct WorldClock;
+
+impl Face for WorldClock {
+    fn draw<D: graphics::Display>(&self, ctx: &FaceContext, disp: &mut D) -> Result<(), D::Error> {
+        super::draw_time(ctx, disp, Vector(0, 0))?;
+
+        let settings = ctx.settings;
+        let home = &ZONES[settings.home_zone as usize % ZONES.len()];
+        let cities = settings.world_zones.iter().filter_map(|idx| *idx);
+        for (i, idx) in cities.enumerate() {
+            let city = &ZONES[idx as usize % ZONES.len()];
+            let time = worldclock::convert(ctx.datetime, home, city);
+            let top = 4 + i as i32 * 10;
+            let area = Rect::new4(COLUMN.start.0 + 4, top, COLUMN.end.0, top + 5);
+
+            // A sign after the city's name shows when it's already
+            // tomorrow or still yesterday there.
+            let day = match time.days {
+                d if d > 0 => "+1",
+                d if d < 0 => "-1",
+                _ => "",
+            };
+            let label = city.label.chars().chain(day.chars());
+            super::draw_mini_in(label, disp, area, Align::Left)?;
+
+            let mut buf = [' '; 6];
+            let len = super::format_time(time.hour, time.minute, settings.hr24, &mut buf);
+            super::draw_mini_in(buf[..len].iter().cloned(), disp, area, Align::Right)?;
+        }
+        super::draw_battery(ctx, disp, BatteryPos::BottomRight)
+    }
+}
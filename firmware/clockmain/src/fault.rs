@@ -0,0 +1,97 @@
+//! Hardware failures that the app can't recover from by itself, and the
+//! screen that reports them in place of the face.
+//!
+//! Failures that might be momentary, like a glitch on the clock chip's bus,
+//! are retried first. Only once retrying hasn't helped does the app show
+//! the error screen, which stays until a button dismisses it or, for the
+//! clock, until it starts responding again.
+
+use crate::blockfont::BlockFont;
+use crate::minifont::MiniFont;
+use graphics::layout::{self, Align, Overflow};
+use graphics::marquee::Marquee;
+use graphics::vector::Rect;
+
+/// The number of times the app tries to read or set the clock, one
+/// straight after another, before counting the attempt as failed.
+pub const CLOCK_RETRIES: u8 = 3;
+
+/// The number of ticks in a row on which reading the clock must fail
+/// before the error screen appears. Until then the time just stands still.
+pub const CLOCK_FAULT_TICKS: u8 = 4;
+
+/// The number of temperature readings in a row that must fail before a
+/// platform reports `Fault::Thermometer`. Until then the last reading that
+/// worked stays on the display.
+pub const THERMOMETER_FAULT_READINGS: u8 = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The clock chip isn't returning the time.
+    ClockRead,
+    /// The clock chip didn't accept a new time from the menu.
+    ClockWrite,
+    /// The saved settings couldn't be read, so the defaults are in use.
+    StorageLoad,
+    /// Changed settings couldn't be saved, so they'll be lost when the
+    /// clock is switched off.
+    StorageSave,
+    /// The thermometer isn't returning readings, so the temperature shown
+    /// is out of date.
+    Thermometer,
+}
+
+impl Fault {
+    /// Returns the single-digit number shown on the error screen, which
+    /// stays the same across firmware versions so that it can be looked up.
+    pub fn code(self) -> u8 {
+        match self {
+            Fault::ClockRead => 1,
+            Fault::ClockWrite => 2,
+            Fault::StorageLoad => 3,
+            Fault::StorageSave => 4,
+            Fault::Thermometer => 5,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Fault::ClockRead => "CLOCK NOT RESPONDING",
+            Fault::ClockWrite => "COULD NOT SET CLOCK",
+            Fault::StorageLoad => "COULD NOT LOAD SETTINGS",
+            Fault::StorageSave => "COULD NOT SAVE SETTINGS",
+            Fault::Thermometer => "THERMOMETER NOT RESPONDING",
+        }
+    }
+}
+
+/// Creates the marquee that shows a fault's description on the error
+/// screen. The descriptions are too long for the block font to show all at
+/// once, so they scroll, and the app should step the marquee on each frame
+/// while the error screen is showing.
+pub fn description_marquee(fault: Fault) -> Marquee<'static, BlockFont> {
+    let area = Rect::new4(0, 22, 256, 42);
+    Marquee::new(BlockFont, fault.description(), area)
+        .with_speed(2)
+        .with_pause(25)
+}
+
+/// Draws the error screen for the given fault, whose description is shown
+/// by the given marquee.
+pub fn draw_fault<D: graphics::Display>(
+    fault: Fault,
+    description: &Marquee<BlockFont>,
+    disp: &mut D,
+) -> Result<(), D::Error> {
+    let title = ['E', 'R', 'R', 'O', 'R', ' ', (b'0' + fault.code()) as char];
+    let area = Rect::new4(0, 4, 256, 9);
+    let chars = title.iter().cloned();
+    layout::draw_chars_in(&MiniFont, chars, disp, area, Align::Center, Overflow::Clip)?;
+
+    description.draw(disp)?;
+
+    let area = Rect::new4(0, 55, 256, 60);
+    let hint = "PRESS ANY BUTTON TO DISMISS";
+    layout::draw_text_in(&MiniFont, hint, disp, area, Align::Center, Overflow::Clip)?;
+    Ok(())
+}
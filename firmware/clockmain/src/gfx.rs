@@ -1,7 +1,10 @@
-use crate::blockfont::Glyph;
+use crate::blockfont::BlockFont;
 use crate::digitfont::Digit;
+use crate::minifont::MiniFont;
 use crate::tiles::Tile;
+use graphics::font::{self, Font};
 use graphics::vector::Vector;
+use graphics::TILE_SIZE;
 
 pub fn draw_big_digit<Display: graphics::Display>(
     num: u8,
@@ -12,43 +15,47 @@ pub fn draw_big_digit<Display: graphics::Display>(
     draw_big_digit_raw(digit, disp, top_left)
 }
 
-pub fn draw_block_text<'s, Display: graphics::Display>(
-    msg: &'s [u8],
+/// Draws the given text in the block font with its top-left corner at the
+/// given position in tiles, returning the width of the text in tiles.
+///
+/// The width is what `graphics::font::measure` reports, converted to
+/// tiles, so it includes the one-tile gaps between characters but not one
+/// after the last. Another piece of text should therefore start at least
+/// `width + 1` tiles to the right.
+pub fn draw_block_text<Display: graphics::Display>(
+    msg: &str,
     disp: &mut Display,
     top_left: Vector,
 ) -> Result<i32, Display::Error> {
-    let mut next_pos = top_left;
-    let mut total_w = 0;
-    for ch in msg {
-        let w = draw_block_char(*ch, disp, next_pos)?;
-        total_w += w;
-        next_pos.0 += w + 1;
-    }
-    Ok(total_w)
+    let w = font::draw_text(&BlockFont, msg, disp, top_left * TILE_SIZE)?;
+    Ok(w / TILE_SIZE)
 }
 
-pub fn draw_block_char<Display: graphics::Display>(
-    ch: u8,
+/// Draws the given text in the mini font with its top-left corner at the
+/// given position, which is in pixels rather than tiles. Returns the
+/// advance width of the text in pixels, which includes the gap after the
+/// last character, so more text can be drawn straight after it.
+pub fn draw_mini_text<Display: graphics::Display>(
+    msg: &str,
     disp: &mut Display,
     top_left: Vector,
 ) -> Result<i32, Display::Error> {
-    let mut w = 5;
-    let glyph = Glyph::get(ch as char);
-
-    for ty in 0..5 {
-        for tx in 0..6 {
-            let tile_idx = glyph.get_tile_idx(tx, ty);
-            if tile_idx != 0 {
-                let tile = Tile::get_by_index(tile_idx as usize);
-                disp.draw_tile(tile, Vector(tx + top_left.0, ty + top_left.1))?;
-                if tx == 6 {
-                    w = 6;
-                }
-            }
-        }
-    }
+    let w = font::draw_text(&MiniFont, msg, disp, top_left)?;
+    Ok(if w > 0 { w + MiniFont.spacing() } else { 0 })
+}
 
-    Ok(w)
+/// Draws a single character in the mini font with its top-left corner at
+/// the given position in pixels, returning its advance width in pixels.
+/// Every tile the glyph touches is replaced, so neighbouring characters
+/// that might share a tile should be drawn together with `draw_mini_text`.
+pub fn draw_mini_char<Display: graphics::Display>(
+    ch: char,
+    disp: &mut Display,
+    top_left: Vector,
+) -> Result<i32, Display::Error> {
+    let glyph = MiniFont.glyph(ch);
+    MiniFont.draw_glyph(glyph, disp, top_left)?;
+    Ok(MiniFont.advance(glyph))
 }
 
 pub fn draw_big_digit_raw<Display: graphics::Display>(
@@ -245,3 +252,34 @@ pub fn draw_colon<Display: graphics::Display>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphics::framebuffer::FrameBuffer;
+
+    #[test]
+    fn block_text_accents() {
+        // The accented letter has a glyph of its own, which is the same
+        // width as the plain one but doesn't look the same.
+        let mut accented_buf = [0u8; 512];
+        let mut accented = FrameBuffer::new(Vector(64, 64), &mut accented_buf[..]);
+        let w = draw_block_text("F\u{e9}v", &mut accented, Vector(1, 2)).unwrap();
+        assert_eq!(w, 17);
+
+        let mut plain_buf = [0u8; 512];
+        let mut plain = FrameBuffer::new(Vector(64, 64), &mut plain_buf[..]);
+        assert_eq!(draw_block_text("Fev", &mut plain, Vector(1, 2)), Ok(w));
+        assert!(accented_buf[..] != plain_buf[..]);
+    }
+
+    #[test]
+    fn mini_text_advances() {
+        let mut buf = [0u8; 64];
+        let mut fb = FrameBuffer::new(Vector(64, 8), &mut buf[..]);
+        assert_eq!(draw_mini_char('A', &mut fb, Vector(0, 0)), Ok(6));
+        assert_eq!(draw_mini_char(',', &mut fb, Vector(6, 0)), Ok(3));
+        assert_eq!(draw_mini_text("A,", &mut fb, Vector(0, 0)), Ok(9));
+        assert_eq!(draw_mini_text("", &mut fb, Vector(0, 0)), Ok(0));
+    }
+}
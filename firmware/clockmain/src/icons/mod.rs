@@ -0,0 +1,3 @@
+use graphics::bitmap::Bitmap;
+
+pub static BATTERY: Bitmap = graphics::include_pbm!("src/icons/battery.pbm");
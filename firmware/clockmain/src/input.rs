@@ -0,0 +1,194 @@
+//! Turns the raw state of the clock's buttons into higher-level button
+//! events.
+
+/// Identifies one of the clock's three buttons.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+    Up,
+    Down,
+    Select,
+}
+
+/// Describes how a button was pressed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Press {
+    /// The button was pressed and then released before it counted as a
+    /// long press.
+    Short,
+    /// The button has been held down for a while. This is reported only
+    /// once per press, while the button is still down.
+    Long,
+    /// The button is still held down some time after a long press. This is
+    /// reported repeatedly for as long as the button stays down.
+    Repeat,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ButtonEvent {
+    pub button: Button,
+    pub press: Press,
+}
+
+impl ButtonEvent {
+    pub fn new(button: Button, press: Press) -> Self {
+        Self {
+            button: button,
+            press: press,
+        }
+    }
+}
+
+/// The number of samples a button must be held for to count as a long
+/// press, which is one second when sampling on each `Event::Frame`.
+pub const LONG_PRESS_SAMPLES: u16 = 25;
+
+/// The number of samples between repeats once a button has been held for
+/// a long press.
+pub const REPEAT_SAMPLES: u16 = 5;
+
+/// Detects short presses, long presses and repeats from regular samples of
+/// which button, if any, is currently held down.
+///
+/// Only one button is tracked at a time. If a different button is sampled
+/// while one is held, that counts as releasing the first, and the second
+/// starts a new press from the following sample if it's still held.
+/// Platforms should call `sample` at a steady rate, such as on each
+/// animation frame. Sampling at that rate also serves to debounce
+/// mechanical switches.
+pub struct ButtonTracker {
+    held: Option<Button>,
+    samples: u16,
+}
+
+impl ButtonTracker {
+    pub fn new() -> Self {
+        Self {
+            held: None,
+            samples: 0,
+        }
+    }
+
+    /// Returns true if no button is being held, so that platforms can stop
+    /// sampling until the next press.
+    pub fn is_idle(&self) -> bool {
+        self.held.is_none()
+    }
+
+    /// Records a new sample, returning an event if one is due.
+    pub fn sample(&mut self, pressed: Option<Button>) -> Option<ButtonEvent> {
+        let held = match self.held {
+            Some(held) => held,
+            None => {
+                self.held = pressed;
+                self.samples = 0;
+                return None;
+            }
+        };
+
+        if pressed != Some(held) {
+            // Releasing the button ends the press, and it's only a short
+            // press if we didn't already report it as a long one. A press
+            // that lasted only a single sample is probably switch bounce.
+            let short = self.samples > 0 && self.samples < LONG_PRESS_SAMPLES;
+            self.held = None;
+            return if short {
+                Some(ButtonEvent::new(held, Press::Short))
+            } else {
+                None
+            };
+        }
+
+        self.samples = self.samples.saturating_add(1);
+        if self.samples == LONG_PRESS_SAMPLES {
+            Some(ButtonEvent::new(held, Press::Long))
+        } else if self.samples > LONG_PRESS_SAMPLES
+            && (self.samples - LONG_PRESS_SAMPLES).is_multiple_of(REPEAT_SAMPLES)
+        {
+            Some(ButtonEvent::new(held, Press::Repeat))
+        } else {
+            None
+        }
+    }
+}
+
+impl core::default::Default for ButtonTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Holds the given button for the given number of samples, returning
+    // the events reported and the sample each was reported on, counting
+    // from one for the sample on which the button went down.
+    fn hold(
+        tracker: &mut ButtonTracker,
+        button: Button,
+        samples: u16,
+    ) -> [Option<(u16, Press)>; 8] {
+        let mut events = [None; 8];
+        let mut count = 0;
+        for i in 1..=samples {
+            if let Some(evt) = tracker.sample(Some(button)) {
+                assert_eq!(evt.button, button);
+                events[count] = Some((i, evt.press));
+                count += 1;
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn short_press() {
+        let mut tracker = ButtonTracker::new();
+        assert!(tracker.is_idle());
+        assert_eq!(tracker.sample(None), None);
+        assert_eq!(hold(&mut tracker, Button::Up, 3), [None; 8]);
+        assert!(!tracker.is_idle());
+        let evt = tracker.sample(None);
+        assert_eq!(evt, Some(ButtonEvent::new(Button::Up, Press::Short)));
+        assert!(tracker.is_idle());
+    }
+
+    #[test]
+    fn bounce_is_ignored() {
+        let mut tracker = ButtonTracker::new();
+        assert_eq!(hold(&mut tracker, Button::Select, 1), [None; 8]);
+        assert_eq!(tracker.sample(None), None);
+        assert!(tracker.is_idle());
+    }
+
+    #[test]
+    fn long_press_and_repeat() {
+        let mut tracker = ButtonTracker::new();
+        let long = LONG_PRESS_SAMPLES + 1;
+        let samples = long + 3 * REPEAT_SAMPLES;
+        let mut expected = [None; 8];
+        expected[0] = Some((long, Press::Long));
+        for (i, evt) in expected[1..4].iter_mut().enumerate() {
+            *evt = Some((long + (i as u16 + 1) * REPEAT_SAMPLES, Press::Repeat));
+        }
+        assert_eq!(hold(&mut tracker, Button::Down, samples), expected);
+
+        // Letting go after a long press doesn't also make a short one.
+        assert_eq!(tracker.sample(None), None);
+        assert!(tracker.is_idle());
+    }
+
+    #[test]
+    fn one_button_at_a_time() {
+        let mut tracker = ButtonTracker::new();
+        assert_eq!(hold(&mut tracker, Button::Up, 2), [None; 8]);
+        // Pressing another button while holding the first counts as letting
+        // go of the first.
+        let evt = tracker.sample(Some(Button::Down));
+        assert_eq!(evt, Some(ButtonEvent::new(Button::Up, Press::Short)));
+        assert_eq!(tracker.sample(None), None);
+        assert_eq!(hold(&mut tracker, Button::Down, 2), [None; 8]);
+        let evt = tracker.sample(None);
+        assert_eq!(evt, Some(ButtonEvent::new(Button::Down, Press::Short)));
+    }
+}
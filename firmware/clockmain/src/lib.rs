@@ -1,152 +1,952 @@
 #![no_std]
-#![feature(const_str_as_bytes)]
 
-use bcdtime::DateTime;
-use graphics::vector::Vector;
+use crate::alarm::{AlarmState, NextAlarm};
+use crate::anim::{DigitAnimation, Transition};
+use crate::blockfont::BlockFont;
+use crate::digitfont::{BigGlyph, Digit};
+use crate::dimming::Dimming;
+use crate::faces::{DateFormat, FaceContext, FaceId, SecondsStyle};
+use crate::fault::Fault;
+use crate::input::{Button, ButtonEvent, Press};
+use crate::locale::Language;
+use crate::menu::{Editor, MenuItem, Mode};
+use crate::settings::{NoStorage, Settings};
+use crate::timer::{Countdown, Stopwatch};
+use bcdtime::{DateTime, BCD};
+use graphics::marquee::Marquee;
 
+pub use crate::event::{Event, EventQueue};
+
+pub mod alarm;
+pub mod anim;
+pub mod burnin;
 pub mod blockfont;
 pub mod digitfont;
+pub mod dimming;
+pub mod event;
+pub mod faces;
+pub mod fault;
 pub mod gfx;
+pub mod icons;
+pub mod input;
+pub mod locale;
+pub mod menu;
 pub mod minifont;
+pub mod settings;
 pub mod tiles;
+pub mod timer;
+pub mod worldclock;
 
-const WEEKDAY: [&'static [u8]; 7] = [
-    "MON".as_bytes(),
-    "TUE".as_bytes(),
-    "WED".as_bytes(),
-    "THU".as_bytes(),
-    "FRI".as_bytes(),
-    "SAT".as_bytes(),
-    "SUN".as_bytes(),
-];
-
-pub struct App<Clock, Display>
+pub struct App<Clock, Display, Storage = NoStorage>
 where
     Clock: bcdtime::Read + bcdtime::Write,
     Display: graphics::Display,
+    Storage: settings::Storage,
 {
     clock: Clock,
     display: Display,
+    storage: Storage,
+    settings: Settings,
     colon: bool,
     battery: bool,
+    temperature: Option<i16>,
+    // The most recent ambient light reading, if there has been one.
+    light: Option<u8>,
+    // The brightness most recently given to the display, if any.
+    brightness: Option<u8>,
     datetime: DateTime,
+    digits: [DigitAnimation; 4],
+    transition: Transition,
+    mode: Mode,
+    idle_ticks: u8,
+    // The number of ticks until the screen saver may return after a button
+    // press woke the clock.
+    wake_ticks: u8,
+    alarm: AlarmState,
+    next_alarm: Option<NextAlarm>,
+    buzzer: bool,
+    countdown: Countdown,
+    stopwatch: Stopwatch,
+    // The failure being shown on the error screen, if any.
+    fault: Option<Fault>,
+    // Scrolls the description of the fault on the error screen. It's left
+    // over from an earlier fault, or a placeholder, when there's no fault.
+    fault_text: Marquee<'static, BlockFont>,
+    // The number of ticks in a row on which the clock couldn't be read.
+    clock_failures: u8,
+    // The number of ticks left before changed settings are saved, or zero
+    // if there's nothing to save.
+    save_ticks: u8,
+    // Set when something has changed that might change what's on the
+    // display, and cleared by a successful redraw.
+    dirty: bool,
+}
+
+impl<Clock, Display> App<Clock, Display, NoStorage>
+where
+    Clock: bcdtime::Read + bcdtime::Write,
+    Display: graphics::Display,
+{
+    /// Creates an app that starts with the default settings and forgets
+    /// any changes to them when switched off.
+    pub fn new(clock: Clock, display: Display) -> Self {
+        Self::with_storage(clock, display, NoStorage)
+    }
 }
 
-impl<Clock, Display> App<Clock, Display>
+impl<Clock, Display, Storage> App<Clock, Display, Storage>
 where
     Clock: bcdtime::Read + bcdtime::Write,
     Display: graphics::Display,
+    Storage: settings::Storage,
 {
-    pub fn new(mut clock: Clock, display: Display) -> Self {
-        let init_time = clock.read().unwrap();
+    /// Creates an app that loads its settings from the given storage, and
+    /// saves them back there a few seconds after they change.
+    pub fn with_storage(mut clock: Clock, display: Display, mut storage: Storage) -> Self {
+        let mut fault = None;
+        let mut clock_failures = 0;
+        let init_time = match read_clock(&mut clock) {
+            Some(dt) => fix_weekday(&mut clock, dt),
+            None => {
+                // We need some time to start from, so we show midnight on
+                // the first day of the century until the clock responds.
+                fault = Some(Fault::ClockRead);
+                clock_failures = fault::CLOCK_FAULT_TICKS;
+                unknown_time()
+            }
+        };
+
+        let mut raw = [0u8; settings::SIZE];
+        let settings = match storage.load(&mut raw[..]) {
+            Ok(true) => Settings::decode(&raw[..]).unwrap_or_default(),
+            Ok(false) => Settings::default(),
+            Err(_) => {
+                fault = fault.or(Some(Fault::StorageLoad));
+                Settings::default()
+            }
+        };
+        let glyphs = time_glyphs(&init_time, settings.hr24);
+        let alarm = AlarmState::new();
+        let next_alarm = alarm.next(&init_time, &settings.alarms);
 
         Self {
             clock: clock,
             display: display,
+            storage: storage,
+            settings: settings,
             colon: false,
             battery: false,
+            temperature: None,
+            light: None,
+            brightness: None,
             datetime: init_time,
+            digits: [
+                DigitAnimation::new(glyphs[0]),
+                DigitAnimation::new(glyphs[1]),
+                DigitAnimation::new(glyphs[2]),
+                DigitAnimation::new(glyphs[3]),
+            ],
+            transition: Transition::Cut,
+            mode: Mode::Clock,
+            idle_ticks: 0,
+            wake_ticks: 0,
+            alarm: alarm,
+            next_alarm: next_alarm,
+            buzzer: false,
+            countdown: Countdown::new(),
+            stopwatch: Stopwatch::new(),
+            fault: fault,
+            fault_text: fault::description_marquee(fault.unwrap_or(Fault::ClockRead)),
+            clock_failures: clock_failures,
+            save_ticks: 0,
+            dirty: true,
+        }
+    }
+
+    /// Selects how the digits of the time change when it advances. Digits
+    /// change immediately by default.
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    /// Returns true if the display may be out of date, in which case the
+    /// caller should call `redraw`. Platforms can skip redrawing otherwise,
+    /// to save time and bus traffic.
+    pub fn needs_redraw(&self) -> bool {
+        self.dirty
+    }
+
+    /// Returns true if any part of the display is animating, in which case
+    /// the caller should deliver `Event::Frame` regularly.
+    pub fn is_animating(&self) -> bool {
+        let stopwatch = self.stopwatch.is_running() || self.stopwatch.is_showing_lap();
+        self.digits.iter().any(|digit| digit.is_running())
+            || (stopwatch && self.is_stopwatch_visible())
+            || self.is_fault_scrolling()
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Provides access to the clock, for platforms whose clock chip has
+    /// other features such as a thermometer.
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    /// Returns when an alarm will next ring, for platforms whose clock chip
+    /// can wake them up at that time. `Outputs::alarm_changed` reports
+    /// when this changes.
+    pub fn next_alarm(&self) -> Option<NextAlarm> {
+        self.next_alarm
+    }
+
+    /// Returns the failure that the error screen is showing, if any, for
+    /// platforms that can also report it some other way.
+    pub fn fault(&self) -> Option<Fault> {
+        self.fault
+    }
+
+    /// Shows the error screen for a failure that the platform found itself,
+    /// in hardware that it reads directly rather than through the app.
+    pub fn report_fault(&mut self, fault: Fault) {
+        self.show_fault(fault);
+        self.dirty = true;
+    }
+
+    // Advance the app's state machine based on the events queued since the
+    // last call, returning any changes the platform should make to its
+    // other outputs as a result.
+    pub fn update(&mut self, events: &EventQueue) -> Outputs {
+        while let Some(evt) = events.pop() {
+            self.handle_event(evt);
+        }
+
+        // The buzzer beeps in time with the colon while the alarm rings.
+        let mut outputs = Outputs::default();
+        let buzzer = self.alarm.is_ringing() && self.colon;
+        if buzzer != self.buzzer {
+            self.buzzer = buzzer;
+            outputs.buzzer = Some(buzzer);
+        }
+        let next_alarm = self.alarm.next(&self.datetime, &self.settings.alarms);
+        if next_alarm != self.next_alarm {
+            self.next_alarm = next_alarm;
+            outputs.alarm_changed = true;
+        }
+        outputs
+    }
+
+    fn handle_event(&mut self, evt: Event) {
+        match evt {
+            Event::Tick => self.handle_tick(),
+            Event::Frame => {
+                // The last frame of an animation still needs drawing, so
+                // we check before stepping.
+                self.dirty |= self.is_animating();
+                for digit in self.digits.iter_mut() {
+                    digit.step();
+                }
+                if self.is_stopwatch_visible() {
+                    self.stopwatch.frame();
+                }
+                if self.is_fault_scrolling() {
+                    self.fault_text.step();
+                }
+            }
+            Event::NextFace => {
+                self.switch_face(true);
+                self.dirty = true;
+            }
+            Event::Button(evt) => {
+                self.dirty = true;
+                let saving = self.is_saving();
+                self.idle_ticks = 0;
+                self.wake_ticks = menu::TIMEOUT_TICKS;
+                if self.alarm.is_ringing() {
+                    self.handle_alarm_button(evt);
+                } else if self.fault.is_some() {
+                    // A press just dismisses the error screen, although
+                    // it'll come back if the clock still isn't responding.
+                    self.fault = None;
+                    self.clock_failures = 0;
+                } else if !saving {
+                    // While the screen saver is showing, a press just
+                    // brings back the face.
+                    self.handle_button(evt);
+                }
+            }
+            Event::Light(light) => {
+                self.light = Some(light);
+                self.dirty = true;
+            }
+            // Platforms without a thermometer just never send these.
+            Event::Temperature(temperature) => {
+                if self.temperature != Some(temperature) {
+                    self.temperature = Some(temperature);
+                    self.dirty = true;
+                }
+            }
         }
     }
 
-    // Advance the app's state machine based on events detected since the
-    // last call.
-    pub fn update(&mut self, evts: &Events) {
-        if evts.tick {
-            self.colon = !self.colon;
-            self.datetime = self.clock.read().unwrap();
+    fn handle_tick(&mut self) {
+        // The colon changes on every tick, so there's always something
+        // to redraw.
+        self.dirty = true;
+        self.colon = !self.colon;
+        self.update_time();
+        if self.alarm.tick(&self.datetime, &self.settings.alarms) {
+            // The alarm takes over the display, so anything the user
+            // was doing in the menu is abandoned.
+            self.mode = Mode::Clock;
+        }
+        if !self.is_stopwatch_visible() {
+            // Frames may not be arriving, so the stopwatch counts ticks.
+            self.stopwatch.tick();
+        }
+        if self.countdown.tick() {
+            // The countdown face appears by itself when it expires,
+            // but again not while the alarm is ringing.
+            self.mode = Mode::Clock;
+        }
+        self.wake_ticks = self.wake_ticks.saturating_sub(1);
+        if self.save_ticks > 0 {
+            self.save_ticks -= 1;
+            if self.save_ticks == 0 {
+                self.save_settings();
+            }
+        }
+        if let Mode::Clock = self.mode {
+        } else {
+            self.idle_ticks = self.idle_ticks.saturating_add(1);
+            if self.idle_ticks >= menu::TIMEOUT_TICKS {
+                // Abandon whatever the user was doing, including any
+                // half-finished edit.
+                self.mode = Mode::Clock;
+            }
         }
     }
 
-    pub fn redraw(&mut self) {
+    /// Draws the current state of the app onto the display, returning any
+    /// error from the display itself. The app can't show those, so it's
+    /// up to the platform whether to retry on the next redraw.
+    pub fn redraw(&mut self) -> Result<(), Display::Error> {
+        self.draw()?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn draw(&mut self) -> Result<(), Display::Error> {
+        let saving = self.is_saving();
+        let brightness = if self.alarm.is_ringing() {
+            // Nobody should sleep through an alarm because it's dark.
+            dimming::full_brightness(&self.settings)
+        } else if saving {
+            dimming::NIGHT_BRIGHTNESS
+        } else {
+            dimming::brightness(&self.settings, &self.datetime, self.light)
+        };
         let disp = &mut self.display;
 
-        disp.clear().unwrap();
+        if self.brightness != Some(brightness) {
+            disp.set_brightness(brightness)?;
+            self.brightness = Some(brightness);
+        }
+        disp.set_shift(burnin::shift(&self.datetime))?;
+        disp.clear()?;
 
+        if self.alarm.is_ringing() {
+            alarm::draw_ringing(&self.datetime, self.settings.hr24, self.colon, disp)?;
+            return self.display.flip();
+        }
+        if let Some(fault) = self.fault {
+            fault::draw_fault(fault, &self.fault_text, disp)?;
+            return self.display.flip();
+        }
+
+        match self.mode {
+            Mode::Clock if saving => burnin::draw_saver(&self.datetime, self.settings.hr24, disp)?,
+            Mode::Clock => {
+                let ctx = FaceContext {
+                    datetime: &self.datetime,
+                    colon: self.colon,
+                    battery: self.battery,
+                    temperature: self.temperature,
+                    digits: &self.digits,
+                    countdown: &self.countdown,
+                    stopwatch: &self.stopwatch,
+                    settings: &self.settings,
+                };
+                let face = if self.countdown.is_expired() {
+                    FaceId::Countdown
+                } else {
+                    self.settings.face
+                };
+                face.draw(&ctx, disp)?;
+            }
+            Mode::Menu(item) => menu::draw_menu(item, disp)?,
+            // The field being edited blinks in time with the colon.
+            Mode::Edit(ref editor) => menu::draw_editor(editor, self.colon, disp)?,
+        }
+
+        self.display.flip()
+    }
+
+    // Returns true if the screen saver should be shown instead of the face.
+    fn is_saving(&self) -> bool {
+        if let Mode::Clock = self.mode {
+            let settings = &self.settings;
+            settings.saver
+                && self.wake_ticks == 0
+                && !self.countdown.is_expired()
+                && burnin::is_night(&self.datetime, settings.night_start, settings.night_end)
+        } else {
+            false
+        }
+    }
+
+    // Returns true if the stopwatch face is on the display, which is the
+    // only time the stopwatch counts frames.
+    fn is_stopwatch_visible(&self) -> bool {
+        if self.alarm.is_ringing()
+            || self.fault.is_some()
+            || self.countdown.is_expired()
+            || self.is_saving()
         {
-            let hour_tens = self.datetime.hour.tens() as u8;
-            if hour_tens != 0 {
-                gfx::draw_big_digit(self.datetime.hour.tens() as u8, disp, Vector(0, 0)).unwrap();
+            return false;
+        }
+        if let Mode::Clock = self.mode {
+            self.settings.face == FaceId::Stopwatch
+        } else {
+            false
+        }
+    }
+
+    // Returns true if the error screen is on the display with a description
+    // that is still moving.
+    fn is_fault_scrolling(&self) -> bool {
+        self.fault.is_some() && !self.alarm.is_ringing() && !self.fault_text.is_finished()
+    }
+
+    // Puts up the error screen for the given fault. If it's already showing
+    // then its description carries on scrolling from where it was.
+    fn show_fault(&mut self, fault: Fault) {
+        if self.fault != Some(fault) {
+            self.fault = Some(fault);
+            self.fault_text = fault::description_marquee(fault);
+        }
+    }
+
+    // Arranges for the settings to be saved once they've stopped changing
+    // for a while, so that flicking through faces or menu values wears the
+    // flash only once.
+    fn settings_changed(&mut self) {
+        self.save_ticks = settings::SAVE_DELAY_TICKS;
+    }
+
+    fn save_settings(&mut self) {
+        if self.storage.save(&self.settings.encode()[..]).is_err() {
+            self.show_fault(Fault::StorageSave);
+        }
+    }
+
+    // Rereads the time from the clock. If that keeps failing then the time
+    // on the display stands still, and eventually the error screen shows.
+    fn update_time(&mut self) {
+        match read_clock(&mut self.clock) {
+            Some(dt) => {
+                self.datetime = dt;
+                self.show_time(self.transition);
+                self.clock_failures = 0;
+                if self.fault == Some(Fault::ClockRead) {
+                    self.fault = None;
+                }
             }
-            gfx::draw_big_digit(self.datetime.hour.units() as u8, disp, Vector(10, 0)).unwrap();
-            gfx::draw_big_digit(self.datetime.minute.tens() as u8, disp, Vector(24, 0)).unwrap();
-            gfx::draw_big_digit(self.datetime.minute.units() as u8, disp, Vector(34, 0)).unwrap();
-            if self.colon {
-                gfx::draw_colon(disp, Vector(20, 0)).unwrap();
+            None => {
+                self.clock_failures = self.clock_failures.saturating_add(1);
+                if self.clock_failures >= fault::CLOCK_FAULT_TICKS {
+                    self.show_fault(Fault::ClockRead);
+                }
             }
         }
+    }
 
-        if self.battery {
-            gfx::draw_block_char(0x80, disp, Vector(64 - 5, -1)).unwrap();
+    // Updates the big digits to match the current time, using the given
+    // transition for any that change.
+    fn show_time(&mut self, transition: Transition) {
+        let glyphs = time_glyphs(&self.datetime, self.settings.hr24);
+        for (digit, glyph) in self.digits.iter_mut().zip(glyphs.iter()) {
+            digit.set(*glyph, transition);
         }
+    }
 
-        {
-            let weekday = self.datetime.day as usize;
-            gfx::draw_block_text(&WEEKDAY[weekday][..], disp, Vector(47, 5)).unwrap();
+    fn handle_button(&mut self, evt: ButtonEvent) {
+        let mode = core::mem::replace(&mut self.mode, Mode::Clock);
+        self.mode = match (mode, evt.button, evt.press) {
+            (Mode::Clock, _, _) => self.handle_face_button(evt),
+
+            (Mode::Menu(item), Button::Up, _) => Mode::Menu(item.prev()),
+            (Mode::Menu(item), Button::Down, _) => Mode::Menu(item.next()),
+            (Mode::Menu(item), Button::Select, Press::Short) => {
+                match Editor::new(item, &self.datetime, &self.settings) {
+                    Some(editor) => {
+                        self.colon = true;
+                        Mode::Edit(editor)
+                    }
+                    None => Mode::Clock,
+                }
+            }
+            (Mode::Menu(_), Button::Select, Press::Long) => Mode::Clock,
+
+            (Mode::Edit(mut editor), Button::Up, _) => {
+                editor.adjust(true);
+                self.colon = true;
+                Mode::Edit(editor)
+            }
+            (Mode::Edit(mut editor), Button::Down, _) => {
+                editor.adjust(false);
+                self.colon = true;
+                Mode::Edit(editor)
+            }
+            (Mode::Edit(mut editor), Button::Select, Press::Short) => {
+                if editor.next_field() {
+                    self.apply_edit(&editor);
+                    Mode::Menu(editor.item())
+                } else {
+                    self.colon = true;
+                    Mode::Edit(editor)
+                }
+            }
+            // A long press abandons the edit without changing anything.
+            (Mode::Edit(editor), Button::Select, Press::Long) => Mode::Menu(editor.item()),
+
+            (mode, _, _) => mode,
+        };
+    }
+
+    fn handle_face_button(&mut self, evt: ButtonEvent) -> Mode {
+        if self.countdown.is_expired() {
+            // The first press just dismisses the expired countdown.
+            if evt.press != Press::Repeat {
+                self.countdown.reset();
+            }
+            return Mode::Clock;
         }
 
-        {
-            let date = self.datetime.date;
-            let units = date.units();
-            let tens = date.tens();
-            let ordinal_lig = if tens == 1 {
-                0x7b // th
-            } else {
-                match units {
-                    1 => 0x7c, // st
-                    2 => 0x7d, // nd
-                    3 => 0x7e, // rd
-                    _ => 0x7b, // th
+        match (self.settings.face, evt.button, evt.press) {
+            (_, Button::Select, Press::Long) => return Mode::Menu(MenuItem::ALL[0]),
+
+            // The timer faces use the buttons that the other faces don't.
+            // The countdown also takes short presses of Up and Down to set
+            // it, and the stopwatch takes short presses of Up for laps, as
+            // a long press would only be reported a second late. On those
+            // faces a long press of the button moves to another face.
+            (FaceId::Countdown, Button::Select, Press::Short) => self.countdown.toggle(),
+            (FaceId::Countdown, Button::Up, Press::Short) => self.countdown.adjust(true),
+            (FaceId::Countdown, Button::Down, Press::Short) => self.countdown.adjust(false),
+            (FaceId::Countdown, Button::Up, Press::Long) => self.switch_face(false),
+            (FaceId::Countdown, Button::Down, Press::Long) => self.switch_face(true),
+            (FaceId::Stopwatch, Button::Select, Press::Short) => self.stopwatch.toggle(),
+            (FaceId::Stopwatch, Button::Up, Press::Short) => self.stopwatch.lap_or_reset(),
+            (FaceId::Stopwatch, Button::Up, Press::Long) => self.switch_face(false),
+
+            (_, Button::Up, Press::Short) => self.switch_face(false),
+            (_, Button::Down, Press::Short) => self.switch_face(true),
+            _ => {}
+        }
+        Mode::Clock
+    }
+
+    // Moves to the next face, or to the previous one.
+    fn switch_face(&mut self, next: bool) {
+        self.settings.face = if next {
+            self.settings.face.next()
+        } else {
+            self.settings.face.prev()
+        };
+        self.settings_changed();
+    }
+
+    fn handle_alarm_button(&mut self, evt: ButtonEvent) {
+        match evt.button {
+            Button::Select => self.alarm.stop(),
+            Button::Up | Button::Down => {
+                let minutes = self.settings.snooze_minutes;
+                self.alarm.snooze(&self.datetime, minutes);
+            }
+        }
+    }
+
+    // Saves the result of a completed edit, either into the clock or into
+    // the settings.
+    fn apply_edit(&mut self, editor: &Editor) {
+        let fields = editor.fields();
+        match editor.item() {
+            MenuItem::SetTime => {
+                let mut dt = self.datetime;
+                dt.hour = BCD::from(fields[0].value);
+                dt.minute = BCD::from(fields[1].value);
+                dt.second = BCD::from(0);
+                dt.hr24 = true;
+                self.set_datetime(dt);
+            }
+            MenuItem::SetDate => {
+                let (year, month, date) = (fields[0].value, fields[1].value, fields[2].value);
+                let mut dt = self.datetime;
+                dt.year = BCD::from(year);
+                dt.month = BCD::from(month);
+                dt.date = BCD::from(date);
+                dt.day = bcdtime::weekday(2000 + year as u16, month, date);
+                self.set_datetime(dt);
+            }
+            MenuItem::HourMode => {
+                self.settings.hr24 = fields[0].value != 0;
+                self.settings_changed();
+                self.show_time(Transition::Cut);
+            }
+            MenuItem::DateFormat => {
+                self.settings.date_format =
+                    DateFormat::from_index(fields[0].value).unwrap_or(DateFormat::Ordinal);
+                self.settings_changed();
+            }
+            MenuItem::Seconds => {
+                self.settings.seconds =
+                    SecondsStyle::from_index(fields[0].value).unwrap_or(SecondsStyle::Off);
+                self.settings_changed();
+            }
+            MenuItem::Language => {
+                self.settings.language =
+                    Language::from_index(fields[0].value).unwrap_or(Language::English);
+                self.settings_changed();
+            }
+            MenuItem::Brightness => {
+                self.settings.brightness = fields[0].value;
+                self.settings_changed();
+            }
+            MenuItem::Alarm(idx) => {
+                let alarm = &mut self.settings.alarms[idx as usize % alarm::COUNT];
+                alarm.hour = fields[0].value;
+                alarm.minute = fields[1].value;
+                // The last field is zero for off, and otherwise one more than
+                // the position in `REPEATS` of the days to ring on.
+                let repeat = (fields[2].value as usize)
+                    .checked_sub(1)
+                    .and_then(|idx| alarm::REPEATS.get(idx));
+                alarm.enabled = repeat.is_some();
+                if let Some(&(days, _)) = repeat {
+                    alarm.days = days;
                 }
-            };
-            let ordinal_lig_wid = match ordinal_lig {
-                0x7b => 6, // th
-                0x7d => 6, // nd
-                _ => 5,
-            };
-            if tens != 0 {
-                gfx::draw_block_char(
-                    '0' as u8 + tens as u8,
-                    disp,
-                    Vector(64 - ordinal_lig_wid - 12, 11),
-                )
-                .unwrap();
-            }
-            gfx::draw_block_char(
-                '0' as u8 + units as u8,
-                disp,
-                Vector(64 - ordinal_lig_wid - 6, 11),
-            )
-            .unwrap();
-            gfx::draw_block_char(ordinal_lig, disp, Vector(64 - ordinal_lig_wid, 11)).unwrap();
-        }
-
-        self.display.flip().unwrap();
+                self.settings_changed();
+            }
+            MenuItem::Snooze => {
+                self.settings.snooze_minutes = fields[0].value;
+                self.settings_changed();
+            }
+            MenuItem::Night => {
+                self.settings.night_start = fields[0].value;
+                self.settings.night_end = fields[1].value;
+                self.settings_changed();
+            }
+            MenuItem::Saver => {
+                self.settings.saver = fields[0].value != 0;
+                self.settings_changed();
+            }
+            MenuItem::Dimming => {
+                self.settings.dimming =
+                    Dimming::from_index(fields[0].value).unwrap_or(Dimming::Off);
+                self.settings_changed();
+            }
+            MenuItem::HomeCity => {
+                self.settings.home_zone = fields[0].value;
+                self.settings_changed();
+            }
+            MenuItem::Cities => {
+                let zones = self.settings.world_zones.iter_mut();
+                for (zone, field) in zones.zip(fields.iter()) {
+                    *zone = field.value.checked_sub(1);
+                }
+                self.settings_changed();
+            }
+            MenuItem::Exit => {}
+        }
+    }
+
+    fn set_datetime(&mut self, dt: DateTime) {
+        let written = (0..fault::CLOCK_RETRIES).any(|_| self.clock.write(&dt).is_ok());
+        if !written {
+            self.show_fault(Fault::ClockWrite);
+            return;
+        }
+        self.datetime = dt;
+        self.show_time(Transition::Cut);
     }
 }
 
-pub struct Events {
-    pub tick: bool,
+/// Changes that the app wants the platform to make to its hardware other
+/// than the display, as returned from `App::update`.
+pub struct Outputs {
+    // Set when the buzzer should be switched on or off.
+    pub buzzer: Option<bool>,
+    // Set when the result of `App::next_alarm` has changed.
+    pub alarm_changed: bool,
 }
 
-impl Events {
-    pub fn reset(&mut self) {
-        *self = Self::default();
+impl core::default::Default for Outputs {
+    fn default() -> Self {
+        Self {
+            buzzer: None,
+            alarm_changed: false,
+        }
     }
+}
 
-    pub fn has_pending(&self) -> bool {
-        *self != Self::default()
+// Reads the time from the given clock, trying again straight away if that
+// fails in case the problem was only momentary.
+fn read_clock<Clock: bcdtime::Read>(clock: &mut Clock) -> Option<DateTime> {
+    (0..fault::CLOCK_RETRIES).find_map(|_| clock.read().ok())
+}
+
+// Makes sure that the day of the week the clock keeps matches its date,
+// returning the corrected time. Older firmware wrote the day to the clock
+// chip without converting it to the chip's numbering, and a chip that has
+// never been set can hold any day at all, so we check once at startup and
+// rewrite the clock if it's wrong. If that fails then we'll try again next
+// time, and meanwhile show the right day anyway.
+fn fix_weekday<Clock: bcdtime::Write>(clock: &mut Clock, mut dt: DateTime) -> DateTime {
+    let (year, month, date): (u8, u8, u8) = (dt.year.into(), dt.month.into(), dt.date.into());
+    if !(1..=12).contains(&month) || date < 1 {
+        return dt;
+    }
+    let day = bcdtime::weekday(2000 + year as u16, month, date);
+    if dt.day != day {
+        dt.day = day;
+        let _ = clock.write(&dt);
     }
+    dt
 }
 
-impl core::default::Default for Events {
-    fn default() -> Self {
-        Self { tick: false }
+// Returns the time to show when the clock has never been read.
+fn unknown_time() -> DateTime {
+    DateTime {
+        second: BCD::from(0),
+        minute: BCD::from(0),
+        hour: BCD::from(0),
+        day: bcdtime::weekday(2000, 1, 1),
+        date: BCD::from(1),
+        month: BCD::from(1),
+        year: BCD::from(0),
+        hr24: true,
+    }
+}
+
+// Returns the glyphs for the four big digits showing the time in the given
+// date and time, leaving out any leading zero in the hour.
+fn time_glyphs(dt: &DateTime, hr24: bool) -> [BigGlyph; 4] {
+    let mut hour: u8 = dt.hour.into();
+    if !hr24 {
+        hour %= 12;
+        if hour == 0 {
+            hour = 12;
+        }
     }
+    let hour_tens = hour / 10;
+    [
+        if hour_tens != 0 {
+            BigGlyph::Digit(Digit::get(hour_tens))
+        } else {
+            BigGlyph::Space
+        },
+        BigGlyph::Digit(Digit::get(hour % 10)),
+        BigGlyph::Digit(Digit::get(dt.minute.tens() as u8)),
+        BigGlyph::Digit(Digit::get(dt.minute.units() as u8)),
+    ]
 }
 
-impl core::cmp::PartialEq<Events> for Events {
-    fn eq(&self, other: &Events) -> bool {
-        self.tick == other.tick
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphics::framebuffer::FrameBuffer;
+    use graphics::vector::Vector;
+
+    type TestApp = App<FakeClock, FrameBuffer<[u8; 2048]>>;
+
+    // A clock that's stuck at the same time, which is Wednesday 14 October
+    // 2026 at 09:41:30.
+    struct FakeClock;
+
+    impl bcdtime::Read for FakeClock {
+        type Error = ();
+
+        fn read(&mut self) -> Result<DateTime, ()> {
+            Ok(DateTime {
+                second: BCD::from(30),
+                minute: BCD::from(41),
+                hour: BCD::from(9),
+                day: 2,
+                date: BCD::from(14),
+                month: BCD::from(10),
+                year: BCD::from(26),
+                hr24: true,
+            })
+        }
+    }
+
+    impl bcdtime::Write for FakeClock {
+        type Error = ();
+
+        fn write(&mut self, _dt: &DateTime) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    // Returns an app showing the given face, which has drawn everything
+    // there is to draw for its first tick.
+    fn app(face: FaceId) -> TestApp {
+        let disp = FrameBuffer::new(Vector(256, 64), [0u8; 2048]);
+        let mut app = App::new(FakeClock, disp);
+        app.settings.face = face;
+        send(&mut app, Event::Tick);
+        app.redraw().unwrap();
+        app
+    }
+
+    fn send(app: &mut TestApp, evt: Event) {
+        let events = EventQueue::new();
+        events.push(evt).unwrap();
+        app.update(&events);
+    }
+
+    // Returns the pixels in one row of the display.
+    fn row(app: &TestApp, y: i32) -> [bool; 256] {
+        let mut row = [false; 256];
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = app.display.get_pixel(Vector(x as i32, y));
+        }
+        row
+    }
+
+    #[test]
+    fn frames_alone_dont_redraw() {
+        for &face in FaceId::ALL.iter() {
+            let mut app = app(face);
+            assert!(!app.needs_redraw(), "{:?}", face);
+            for _ in 0..50 {
+                send(&mut app, Event::Frame);
+                assert!(!app.needs_redraw(), "{:?}", face);
+            }
+            assert!(!app.is_animating(), "{:?}", face);
+        }
+    }
+
+    #[test]
+    fn hidden_stopwatch_doesnt_animate() {
+        let mut app = app(FaceId::Classic);
+        app.stopwatch.toggle();
+        assert!(!app.is_animating());
+        for _ in 0..50 {
+            send(&mut app, Event::Frame);
+            assert!(!app.needs_redraw());
+        }
+        // It still keeps time, by counting ticks instead.
+        assert_eq!(app.stopwatch.elapsed(), 0);
+        send(&mut app, Event::Tick);
+        assert_eq!(app.stopwatch.elapsed(), 13);
+        app.redraw().unwrap();
+
+        app.settings.face = FaceId::Stopwatch;
+        assert!(app.is_animating());
+        send(&mut app, Event::Frame);
+        assert!(app.needs_redraw());
+        assert_eq!(app.stopwatch.elapsed(), 14);
+        // Ticks don't count while frames are arriving.
+        send(&mut app, Event::Tick);
+        assert_eq!(app.stopwatch.elapsed(), 14);
+    }
+
+    #[test]
+    fn stopped_stopwatch_doesnt_animate() {
+        let mut app = app(FaceId::Stopwatch);
+        app.stopwatch.toggle();
+        send(&mut app, Event::Frame);
+        app.stopwatch.toggle();
+        app.redraw().unwrap();
+        assert!(!app.is_animating());
+        send(&mut app, Event::Frame);
+        assert!(!app.needs_redraw());
+        assert_eq!(app.stopwatch.elapsed(), 1);
+    }
+
+    #[test]
+    fn fault_description_scrolls() {
+        let mut app = app(FaceId::Classic);
+        app.show_fault(Fault::ClockWrite);
+        app.redraw().unwrap();
+        assert!(app.is_animating());
+        let start = row(&app, 30);
+
+        // The description waits a second before it starts to move.
+        for _ in 0..25 {
+            send(&mut app, Event::Frame);
+        }
+        app.redraw().unwrap();
+        assert!(row(&app, 30) == start);
+        send(&mut app, Event::Frame);
+        app.redraw().unwrap();
+        let moved = row(&app, 30);
+        assert!(moved != start);
+
+        // The same fault happening again doesn't restart it, but
+        // dismissing the error screen stops it.
+        app.show_fault(Fault::ClockWrite);
+        app.redraw().unwrap();
+        assert!(row(&app, 30) == moved);
+        let press = ButtonEvent::new(Button::Select, Press::Short);
+        send(&mut app, Event::Button(press));
+        assert!(!app.is_animating());
+    }
+
+    #[test]
+    fn lap_records_split() {
+        let mut app = app(FaceId::Stopwatch);
+        let press = |button, press| Event::Button(ButtonEvent::new(button, press));
+        send(&mut app, press(Button::Select, Press::Short));
+        for _ in 0..40 {
+            send(&mut app, Event::Frame);
+        }
+        // The lap ends when the button is let go, which is as soon as the
+        // press is reported.
+        send(&mut app, press(Button::Up, Press::Short));
+        assert_eq!(app.stopwatch.lap_count(), 1);
+        assert_eq!(app.stopwatch.lap(0), Some(40));
+        for _ in 0..10 {
+            send(&mut app, Event::Frame);
+        }
+        send(&mut app, press(Button::Up, Press::Short));
+        assert_eq!(app.stopwatch.lap(0), Some(10));
+        assert_eq!(app.stopwatch.lap(1), Some(40));
+
+        // A long press leaves the laps alone and moves to another face.
+        send(&mut app, press(Button::Up, Press::Long));
+        assert_eq!(app.stopwatch.lap_count(), 2);
+        assert_eq!(app.settings.face, FaceId::Stopwatch.prev());
+    }
+
+    #[test]
+    fn reported_fault_shows_until_dismissed() {
+        let mut app = app(FaceId::Temperature);
+        app.report_fault(Fault::Thermometer);
+        assert!(app.needs_redraw());
+        assert_eq!(app.fault(), Some(Fault::Thermometer));
+
+        // Unlike a clock fault, it stays through ticks on which the clock
+        // reads fine.
+        send(&mut app, Event::Tick);
+        assert_eq!(app.fault(), Some(Fault::Thermometer));
+        let press = ButtonEvent::new(Button::Down, Press::Short);
+        send(&mut app, Event::Button(press));
+        assert_eq!(app.fault(), None);
+        assert_eq!(app.settings.face, FaceId::Temperature);
     }
 }
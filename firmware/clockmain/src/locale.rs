@@ -0,0 +1,190 @@
+//! The names and conventions that differ between the languages the clock
+//! can write dates in.
+//!
+//! Names are written with only their first letter capitalized, because
+//! the block font only has accents on its lowercase letters.
+
+use bcdtime::BCD;
+
+/// The languages that the user can choose between.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+    ];
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(idx: u8) -> Option<Self> {
+        Self::ALL.get(idx as usize).cloned()
+    }
+
+    /// Returns the name of the language in that language.
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::French => "Fran\u{e7}ais",
+            Language::Spanish => "Espa\u{f1}ol",
+        }
+    }
+
+    pub fn locale(self) -> &'static Locale {
+        match self {
+            Language::English => &ENGLISH,
+            Language::German => &GERMAN,
+            Language::French => &FRENCH,
+            Language::Spanish => &SPANISH,
+        }
+    }
+}
+
+/// How dates are written in one language.
+pub struct Locale {
+    /// Abbreviations of the days of the week, starting from Monday.
+    pub weekdays: [&'static str; 7],
+    /// Three-letter abbreviations of the months, starting from January.
+    pub months: [&'static str; 12],
+    /// Whether the day of the month comes before the month, as in "18 Okt"
+    /// rather than "Oct 18".
+    pub day_first: bool,
+    /// Returns what follows the day of the month when it's written on its
+    /// own, which is at most two characters.
+    pub ordinal_suffix: fn(BCD) -> &'static str,
+}
+
+impl Locale {
+    /// Returns the abbreviation of the given day of the week, counting from
+    /// 0 for Monday as `DateTime::day` does.
+    pub fn weekday(&self, day: u8) -> &'static str {
+        self.weekdays[day as usize % self.weekdays.len()]
+    }
+
+    /// Returns the abbreviation of the given month, counting from 1 for
+    /// January, or "???" if there's no such month.
+    pub fn month(&self, month: BCD) -> &'static str {
+        let month = month.tens() * 10 + month.units();
+        match month {
+            1..=12 => self.months[month as usize - 1],
+            _ => "???",
+        }
+    }
+}
+
+// The plain letter that stands in for each character from U+00C0 to U+00FF
+// in fonts that don't have it, or the placeholder character 127 if there's
+// no sensible stand-in.
+static UNACCENTED: &[u8; 64] =
+    b"AAAAAA\x7fCEEEEIIIIDNOOOOO\x7fOUUUUY\x7fsaaaaaa\x7fceeeeiiiidnooooo\x7fouuuuy\x7fy";
+
+/// Returns the plain letter that fonts without accents should draw in
+/// place of the given Latin-1 letter, or the placeholder character 127 if
+/// there isn't one. Other characters are returned unchanged.
+pub fn unaccented(ch: char) -> char {
+    match ch as u32 {
+        v @ 0xc0..=0xff => UNACCENTED[v as usize - 0xc0] as char,
+        _ => ch,
+    }
+}
+
+pub static ENGLISH: Locale = Locale {
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    day_first: false,
+    ordinal_suffix: english_suffix,
+};
+
+pub static GERMAN: Locale = Locale {
+    weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    months: [
+        "Jan", "Feb", "M\u{e4}r", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+    ],
+    day_first: true,
+    ordinal_suffix: german_suffix,
+};
+
+pub static FRENCH: Locale = Locale {
+    weekdays: ["Lun", "Mar", "Mer", "Jeu", "Ven", "Sam", "Dim"],
+    months: [
+        "Jan", "F\u{e9}v", "Mar", "Avr", "Mai", "Jun", "Jul", "Ao\u{fb}", "Sep", "Oct", "Nov",
+        "D\u{e9}c",
+    ],
+    day_first: true,
+    ordinal_suffix: french_suffix,
+};
+
+pub static SPANISH: Locale = Locale {
+    weekdays: ["Lun", "Mar", "Mi\u{e9}", "Jue", "Vie", "S\u{e1}b", "Dom"],
+    months: [
+        "Ene", "Feb", "Mar", "Abr", "May", "Jun", "Jul", "Ago", "Sep", "Oct", "Nov", "Dic",
+    ],
+    day_first: true,
+    ordinal_suffix: spanish_suffix,
+};
+
+// Returns the block font ligature for the English ordinal suffix, which
+// only the block font has.
+fn english_suffix(date: BCD) -> &'static str {
+    if date.tens() == 1 {
+        return "\u{7b}"; // th
+    }
+    match date.units() {
+        1 => "\u{7c}", // st
+        2 => "\u{7d}", // nd
+        3 => "\u{7e}", // rd
+        _ => "\u{7b}", // th
+    }
+}
+
+// German writes ordinal numbers with a full stop, as in "18.".
+fn german_suffix(_: BCD) -> &'static str {
+    "."
+}
+
+// French only marks the first of the month, as in "1er".
+fn french_suffix(date: BCD) -> &'static str {
+    if date.tens() == 0 && date.units() == 1 {
+        "er"
+    } else {
+        ""
+    }
+}
+
+// Spanish writes the day of the month as a plain number.
+fn spanish_suffix(_: BCD) -> &'static str {
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unaccented_letters() {
+        assert_eq!(unaccented('\u{c0}'), 'A');
+        assert_eq!(unaccented('\u{c7}'), 'C');
+        assert_eq!(unaccented('\u{e9}'), 'e');
+        assert_eq!(unaccented('\u{f1}'), 'n');
+        assert_eq!(unaccented('\u{ff}'), 'y');
+        // Latin-1 characters with no plain stand-in.
+        assert_eq!(unaccented('\u{c6}'), '\u{7f}');
+        assert_eq!(unaccented('\u{f7}'), '\u{7f}');
+        // Anything else is left alone.
+        assert_eq!(unaccented('e'), 'e');
+        assert_eq!(unaccented('\u{20ac}'), '\u{20ac}');
+    }
+}
@@ -0,0 +1,502 @@
+//! The menu for configuring the clock using its buttons.
+
+use crate::alarm::{self, REPEATS};
+use crate::blockfont::BlockFont;
+use crate::dimming::Dimming;
+use crate::faces::{DateFormat, SecondsStyle};
+use crate::locale::Language;
+use crate::minifont::MiniFont;
+use crate::settings::{Settings, BRIGHTNESS_LEVELS};
+use crate::worldclock::{self, MAX_CITIES, ZONES};
+use bcdtime::DateTime;
+use graphics::layout::{self, Align, Overflow};
+use graphics::vector::Rect;
+
+/// The number of ticks without any button presses after which the menu
+/// gives up and returns to the clock, which is thirty seconds.
+pub const TIMEOUT_TICKS: u8 = 60;
+
+/// What the app is currently showing.
+pub enum Mode {
+    /// The selected watch face.
+    Clock,
+    /// The top level of the menu, with the given item highlighted.
+    Menu(MenuItem),
+    /// The screen for changing the setting behind a menu item.
+    Edit(Editor),
+}
+
+/// The items in the menu.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MenuItem {
+    SetTime,
+    SetDate,
+    HourMode,
+    /// Chooses how faces with room for only a short date write it.
+    DateFormat,
+    /// Chooses whether and how faces show the seconds.
+    Seconds,
+    /// Chooses the language that dates are written in.
+    Language,
+    Brightness,
+    /// Changes one of the alarms, identified by its index.
+    Alarm(u8),
+    Snooze,
+    /// Chooses the hours of the night, for the screen saver and dimming.
+    Night,
+    /// Switches the night-time screen saver on or off.
+    Saver,
+    Dimming,
+    /// Chooses the city the clock is in, which the world clock needs to
+    /// know so that it can work out the time elsewhere.
+    HomeCity,
+    /// Chooses the other cities that the world clock shows.
+    Cities,
+    Exit,
+}
+
+impl MenuItem {
+    /// All of the items, in the order they appear in the menu.
+    pub const ALL: [MenuItem; 17] = [
+        MenuItem::SetTime,
+        MenuItem::SetDate,
+        MenuItem::HourMode,
+        MenuItem::DateFormat,
+        MenuItem::Seconds,
+        MenuItem::Language,
+        MenuItem::Brightness,
+        MenuItem::Alarm(0),
+        MenuItem::Alarm(1),
+        MenuItem::Alarm(2),
+        MenuItem::Snooze,
+        MenuItem::Night,
+        MenuItem::Saver,
+        MenuItem::Dimming,
+        MenuItem::HomeCity,
+        MenuItem::Cities,
+        MenuItem::Exit,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MenuItem::SetTime => "SET TIME",
+            MenuItem::SetDate => "SET DATE",
+            MenuItem::HourMode => "HOURS",
+            MenuItem::DateFormat => "DATE STYLE",
+            MenuItem::Seconds => "SECONDS",
+            MenuItem::Language => "LANGUAGE",
+            MenuItem::Brightness => "BRIGHTNESS",
+            MenuItem::Alarm(idx) => ALARM_LABELS[idx as usize % alarm::COUNT],
+            MenuItem::Snooze => "SNOOZE",
+            MenuItem::Night => "NIGHT",
+            MenuItem::Saver => "SAVER",
+            MenuItem::Dimming => "DIMMING",
+            MenuItem::HomeCity => "HOME CITY",
+            MenuItem::Cities => "CITIES",
+            MenuItem::Exit => "EXIT",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let idx = self.index();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let idx = self.index();
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|item| *item == self).unwrap_or(0)
+    }
+}
+
+const ALARM_LABELS: [&str; alarm::COUNT] = ["ALARM 1", "ALARM 2", "ALARM 3"];
+
+/// A number being adjusted on an editing screen, which wraps around at the
+/// ends of its range.
+#[derive(Copy, Clone, Debug)]
+pub struct Field {
+    pub value: u8,
+    pub min: u8,
+    pub max: u8,
+}
+
+impl Field {
+    fn new(value: u8, min: u8, max: u8) -> Self {
+        Self {
+            value: value.max(min).min(max),
+            min: min,
+            max: max,
+        }
+    }
+
+    fn step(&mut self, up: bool) {
+        self.value = if up {
+            if self.value >= self.max {
+                self.min
+            } else {
+                self.value + 1
+            }
+        } else if self.value <= self.min {
+            self.max
+        } else {
+            self.value - 1
+        };
+    }
+}
+
+/// The state of an editing screen, which adjusts up to three fields one
+/// after another.
+pub struct Editor {
+    item: MenuItem,
+    fields: [Field; 3],
+    count: usize,
+    current: usize,
+}
+
+impl Editor {
+    /// Starts editing the setting for the given menu item, starting from
+    /// its current value. Returns `None` for items that have nothing to
+    /// edit.
+    pub fn new(item: MenuItem, dt: &DateTime, settings: &Settings) -> Option<Self> {
+        let unused = Field::new(0, 0, 0);
+        let (fields, count) = match item {
+            MenuItem::SetTime => (
+                [
+                    Field::new(dt.hour.into(), 0, 23),
+                    Field::new(dt.minute.into(), 0, 59),
+                    unused,
+                ],
+                2,
+            ),
+            MenuItem::SetDate => {
+                let year: u8 = dt.year.into();
+                let month: u8 = dt.month.into();
+                let max_date = bcdtime::days_in_month(2000 + year as u16, month);
+                (
+                    [
+                        Field::new(year, 0, 99),
+                        Field::new(month, 1, 12),
+                        Field::new(dt.date.into(), 1, max_date),
+                    ],
+                    3,
+                )
+            }
+            MenuItem::HourMode => ([Field::new(settings.hr24 as u8, 0, 1), unused, unused], 1),
+            MenuItem::DateFormat => (
+                [
+                    Field::new(
+                        settings.date_format.index(),
+                        0,
+                        DateFormat::ALL.len() as u8 - 1,
+                    ),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::Seconds => (
+                [
+                    Field::new(
+                        settings.seconds.index(),
+                        0,
+                        SecondsStyle::ALL.len() as u8 - 1,
+                    ),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::Language => (
+                [
+                    Field::new(settings.language.index(), 0, Language::ALL.len() as u8 - 1),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::Brightness => (
+                [
+                    Field::new(settings.brightness, 1, BRIGHTNESS_LEVELS),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::Alarm(idx) => {
+                let alarm = settings.alarms[idx as usize % alarm::COUNT];
+                // The third field selects from `REPEATS`, with zero to
+                // switch the alarm off.
+                let repeat = if alarm.enabled {
+                    alarm::repeat_index(alarm.days).map_or(1, |idx| idx as u8 + 1)
+                } else {
+                    0
+                };
+                (
+                    [
+                        Field::new(alarm.hour, 0, 23),
+                        Field::new(alarm.minute, 0, 59),
+                        Field::new(repeat, 0, REPEATS.len() as u8),
+                    ],
+                    3,
+                )
+            }
+            MenuItem::Snooze => (
+                [
+                    Field::new(settings.snooze_minutes, 1, alarm::MAX_SNOOZE_MINUTES),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::Night => (
+                [
+                    Field::new(settings.night_start, 0, 23),
+                    Field::new(settings.night_end, 0, 23),
+                    unused,
+                ],
+                2,
+            ),
+            MenuItem::Saver => ([Field::new(settings.saver as u8, 0, 1), unused, unused], 1),
+            MenuItem::Dimming => (
+                [
+                    Field::new(settings.dimming.index(), 0, Dimming::ALL.len() as u8 - 1),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::HomeCity => (
+                [
+                    Field::new(settings.home_zone, 0, ZONES.len() as u8 - 1),
+                    unused,
+                    unused,
+                ],
+                1,
+            ),
+            MenuItem::Cities => {
+                // Each field selects a city from `ZONES`, counting from one
+                // so that zero can leave the slot empty.
+                let mut fields = [unused; 3];
+                for (field, zone) in fields.iter_mut().zip(settings.world_zones.iter()) {
+                    let value = zone.map_or(0, |idx| idx + 1);
+                    *field = Field::new(value, 0, ZONES.len() as u8);
+                }
+                (fields, MAX_CITIES)
+            }
+            MenuItem::Exit => return None,
+        };
+        Some(Self {
+            item: item,
+            fields: fields,
+            count: count,
+            current: 0,
+        })
+    }
+
+    pub fn item(&self) -> MenuItem {
+        self.item
+    }
+
+    /// Returns the fields being edited, in the order they are edited.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields[..self.count]
+    }
+
+    /// Returns the index of the field currently being adjusted.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Increases or decreases the current field.
+    pub fn adjust(&mut self, up: bool) {
+        self.fields[self.current].step(up);
+        if self.item == MenuItem::SetDate {
+            // The number of days in the month depends on the year and
+            // month, which are edited before the day.
+            let year = 2000 + self.fields[0].value as u16;
+            let max = bcdtime::days_in_month(year, self.fields[1].value);
+            let date = &mut self.fields[2];
+            date.max = max;
+            date.value = date.value.min(max);
+        }
+    }
+
+    /// Moves on to the next field, returning true if there are no more
+    /// fields and so the edit is complete.
+    pub fn next_field(&mut self) -> bool {
+        self.current += 1;
+        self.current >= self.count
+    }
+}
+
+/// Draws the top level of the menu with the given item highlighted.
+pub fn draw_menu<D: graphics::Display>(item: MenuItem, disp: &mut D) -> Result<(), D::Error> {
+    draw_title("MENU", disp)?;
+    let area = Rect::new4(0, 22, 256, 42);
+    let chars = item.label().chars();
+    layout::draw_chars_in(
+        &BlockFont,
+        chars,
+        disp,
+        area,
+        Align::Center,
+        Overflow::Ellipsis,
+    )?;
+    Ok(())
+}
+
+/// Draws an editing screen. The field being edited is left out when
+/// `blink` is false, so that it flashes when drawn alternately with and
+/// without it.
+pub fn draw_editor<D: graphics::Display>(
+    editor: &Editor,
+    blink: bool,
+    disp: &mut D,
+) -> Result<(), D::Error> {
+    draw_title(editor.item.label(), disp)?;
+
+    let mut text = Text::new();
+    let mut detail = Text::new();
+    for (i, field) in editor.fields().iter().enumerate() {
+        let hidden = i == editor.current && !blink;
+        match editor.item {
+            MenuItem::SetTime | MenuItem::Alarm(_) if i == 1 => text.push(':'),
+            MenuItem::SetDate if i > 0 => text.push('-'),
+            MenuItem::SetDate => text.push_str("20"),
+            MenuItem::Night if i == 1 => text.push('-'),
+            _ => {}
+        }
+        match editor.item {
+            MenuItem::HourMode => {
+                let label = if field.value != 0 { "24H" } else { "12H" };
+                text.push_field(label.chars(), hidden)
+            }
+            MenuItem::DateFormat => {
+                let format = DateFormat::from_index(field.value).unwrap_or(DateFormat::Ordinal);
+                text.push_field(format.label().chars(), hidden)
+            }
+            MenuItem::Seconds => {
+                let style = SecondsStyle::from_index(field.value).unwrap_or(SecondsStyle::Off);
+                text.push_field(style.label().chars(), hidden)
+            }
+            MenuItem::Language => {
+                let language = Language::from_index(field.value).unwrap_or(Language::English);
+                text.push_field(language.label().chars(), hidden)
+            }
+            MenuItem::Brightness => text.push_field(digits(field.value, 1), hidden),
+            MenuItem::Saver => {
+                let label = if field.value != 0 { "ON" } else { "OFF" };
+                text.push_field(label.chars(), hidden)
+            }
+            MenuItem::Dimming => {
+                let dimming = Dimming::from_index(field.value).unwrap_or(Dimming::Off);
+                text.push_field(dimming.label().chars(), hidden)
+            }
+            // The days an alarm rings on don't fit in the block font, so
+            // they go on a separate line.
+            MenuItem::Alarm(_) if i == 2 => {
+                if !hidden {
+                    let repeat = (field.value as usize)
+                        .checked_sub(1)
+                        .and_then(|idx| REPEATS.get(idx));
+                    detail.push_str(repeat.map_or("OFF", |&(_, label)| label));
+                }
+            }
+            // There isn't room for all of the cities at once, so we show
+            // only the one being chosen, with its offset from UTC below.
+            MenuItem::HomeCity | MenuItem::Cities if i == editor.current => {
+                let zone = match editor.item {
+                    MenuItem::HomeCity => Some(&ZONES[field.value as usize]),
+                    _ => field.value.checked_sub(1).map(|idx| &ZONES[idx as usize]),
+                };
+                if editor.item == MenuItem::Cities {
+                    detail.push_str("CITY ");
+                    detail.push_field(digits(i as u8 + 1, 1), false);
+                    detail.push_str("  ");
+                }
+                match zone {
+                    Some(zone) => {
+                        text.push_field(zone.label.chars(), hidden);
+                        let mut buf = [' '; 9];
+                        let offset = worldclock::format_offset(zone.offset, &mut buf);
+                        detail.push_field(offset.iter().cloned(), false);
+                    }
+                    None => {
+                        text.push_field("NONE".chars(), hidden);
+                        detail.push_str("NOT SHOWN");
+                    }
+                }
+            }
+            MenuItem::HomeCity | MenuItem::Cities => {}
+            MenuItem::Snooze => {
+                text.push_field(digits(field.value, 2), hidden);
+                text.push_str(" MIN");
+            }
+            _ => text.push_field(digits(field.value, 2), hidden),
+        }
+    }
+
+    let area = Rect::new4(0, 22, 256, 42);
+    let chars = text.chars();
+    layout::draw_chars_in(&BlockFont, chars, disp, area, Align::Center, Overflow::Clip)?;
+    let area = Rect::new4(0, 50, 256, 55);
+    let chars = detail.chars();
+    layout::draw_chars_in(&MiniFont, chars, disp, area, Align::Center, Overflow::Clip)?;
+    Ok(())
+}
+
+fn draw_title<D: graphics::Display>(title: &str, disp: &mut D) -> Result<(), D::Error> {
+    let area = Rect::new4(0, 4, 256, 9);
+    layout::draw_text_in(&MiniFont, title, disp, area, Align::Center, Overflow::Clip)?;
+    Ok(())
+}
+
+// Returns the last `width` decimal digits of the given number, which may
+// include leading zeros. `width` must be no more than three.
+fn digits(v: u8, width: usize) -> impl Iterator<Item = char> + Clone {
+    let all = [v / 100, (v / 10) % 10, v % 10];
+    (3 - width..3).map(move |i| (b'0' + all[i]) as char)
+}
+
+// A short line of text built up without any heap allocation.
+struct Text {
+    buf: [char; 16],
+    len: usize,
+}
+
+impl Text {
+    fn new() -> Self {
+        Self {
+            buf: [' '; 16],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        if self.len < self.buf.len() {
+            self.buf[self.len] = ch;
+            self.len += 1;
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        for ch in s.chars() {
+            self.push(ch);
+        }
+    }
+
+    // Adds the given characters, or an equal number of spaces if `hidden`
+    // is set, so that blinking doesn't move any of the other text.
+    fn push_field<I: Iterator<Item = char>>(&mut self, chars: I, hidden: bool) {
+        for ch in chars {
+            self.push(if hidden { ' ' } else { ch });
+        }
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + Clone + '_ {
+        self.buf[..self.len].iter().cloned()
+    }
+}
@@ -6,16 +6,18 @@ import struct
 def custom_character_code(ch):
     if ch >= 0x41 and ch <= 0x5a:
         return ch - 0x41
-    if ch >= 0x30 and ch <= 0x39:
+    if ch >= 0x30 and ch <= 0x3a:
         return ch - 0x30 + 26
     if ch >= 0x20 and ch <= 0x21:
-        return ch - 0x20 + 26 + 10
+        return ch - 0x20 + 26 + 11
+    if ch >= 0x2b and ch <= 0x2e:
+        return ch - 0x2b + 26 + 11 + 2
     if ch >= 0x7b:
-        return ch - 0x7b + 26 + 10 + 2
+        return ch - 0x7b + 26 + 11 + 2 + 4
 
 
 descriptors = []
-for i in xrange(26 + 10 + 2 + 5):
+for i in xrange(26 + 11 + 2 + 4 + 5):
     descriptors.append(None)
 
 
@@ -1,26 +1,36 @@
+use graphics::bitmap::tile_pixel_mask;
+use graphics::vector::{Rect, Vector};
+use graphics::TILE_SIZE;
 
 static DESCRIPTORS: &[u8] = include_bytes!("descriptors.bin");
 static COMMANDS: &[u8] = include_bytes!("commands.bin");
 
+/// The height of every glyph in the mini font, in pixels.
+pub const HEIGHT: i32 = 5;
+
 #[inline(always)]
 fn map_ascii_code(ch: u8) -> u8 {
     if ch >= 0x41 && ch <= 0x5a {
         ch - 0x41
-    } else if ch >= 0x30 && ch <= 0x39 {
+    } else if ch >= 0x30 && ch <= 0x3a {
         ch - 0x30 + 26
     } else if ch >= 0x20 && ch <= 0x21 {
-        ch - 0x20 + 26 + 10
+        ch - 0x20 + 26 + 11
+    } else if ch >= 0x2b && ch <= 0x2e {
+        ch - 0x2b + 26 + 11 + 2
     } else if ch >= 0x7b && ch < 128 {
-        ch - 0x7b + 26 + 10 + 2
+        ch - 0x7b + 26 + 11 + 2 + 4
     } else {
-        127 // placeholder character
+        map_ascii_code(127) // placeholder character
     }
 }
 
 pub fn commands_for_character(ch: u8) -> &'static [u8] {
-    let didx = map_ascii_code(ch) as usize;
-    let start = (DESCRIPTORS[didx] as usize) << 8 | (DESCRIPTORS[didx+1] as usize);
-    let end = (DESCRIPTORS[didx+2] as usize) << 8 | (DESCRIPTORS[didx+3] as usize);
+    // Each descriptor is a pair of big-endian 16-bit offsets into the
+    // command stream.
+    let didx = map_ascii_code(ch) as usize * 4;
+    let start = (DESCRIPTORS[didx] as usize) << 8 | (DESCRIPTORS[didx + 1] as usize);
+    let end = (DESCRIPTORS[didx + 2] as usize) << 8 | (DESCRIPTORS[didx + 3] as usize);
     &COMMANDS[start..end]
 }
 
@@ -30,11 +40,255 @@ pub fn decode_command(cmd: u8) -> Command {
     if width == 0 {
         Command::Skip(height)
     } else {
-        Command::Rect { w: width, h: height }
+        Command::Rect {
+            w: width,
+            h: height,
+        }
     }
 }
 
 pub enum Command {
-    Rect{w: u8, h: u8},
+    Rect { w: u8, h: u8 },
     Skip(u8),
 }
+
+/// A glyph in the mini font, represented by its command stream.
+///
+/// The commands describe the glyph column by column, from top to bottom.
+/// A cursor starts in the top-left corner; each `Rect` command fills a
+/// rectangle whose top-left corner is at the cursor, and then both kinds of
+/// command move the cursor down by their height, wrapping to the top of
+/// the next column when it passes the bottom of the glyph.
+#[derive(Copy, Clone, Debug)]
+pub struct Glyph(&'static [u8]);
+
+impl Glyph {
+    pub fn get(ch: char) -> Self {
+        // The font only has capitals, so everything else is drawn as the
+        // nearest one.
+        let ch = crate::locale::unaccented(ch).to_ascii_uppercase();
+        let code = if (ch as u32) < 128 { ch as u8 } else { 127 };
+        Self(commands_for_character(code))
+    }
+
+    /// Returns an iterator over the rectangles that make up the glyph,
+    /// relative to its top-left corner.
+    pub fn rects(self) -> Rects {
+        Rects {
+            cmds: self.0,
+            cursor: 0,
+        }
+    }
+
+    /// Returns the width of the glyph in pixels.
+    pub fn width(self) -> i32 {
+        let rows: i32 = self
+            .0
+            .iter()
+            .map(|cmd| match decode_command(*cmd) {
+                Command::Rect { h, .. } => h as i32,
+                Command::Skip(h) => h as i32,
+            })
+            .sum();
+        rows / HEIGHT
+    }
+}
+
+/// Iterator over the rectangles in a glyph, returned by `Glyph::rects`.
+pub struct Rects {
+    cmds: &'static [u8],
+    // The cursor is tracked as a number of rows from the top of the first
+    // column, so that wrapping into later columns is just division.
+    cursor: i32,
+}
+
+impl Iterator for Rects {
+    type Item = Rect;
+
+    fn next(&mut self) -> Option<Rect> {
+        while let Some((cmd, rest)) = self.cmds.split_first() {
+            self.cmds = rest;
+            let pos = Vector(self.cursor / HEIGHT, self.cursor % HEIGHT);
+            match decode_command(*cmd) {
+                Command::Skip(h) => {
+                    self.cursor += h as i32;
+                }
+                Command::Rect { w, h } => {
+                    self.cursor += h as i32;
+                    return Some(Rect::new_sized(pos, Vector(w as i32, h as i32)));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The mini font, for use with the text functions in `graphics::font`.
+///
+/// Unlike the other fonts, the mini font is designed at the level of
+/// individual pixels rather than tiles: glyphs are five pixels tall and
+/// separated by a one-pixel gap.
+#[derive(Copy, Clone, Debug)]
+pub struct MiniFont;
+
+impl graphics::font::Font for MiniFont {
+    type Glyph = Glyph;
+
+    fn glyph(&self, ch: char) -> Glyph {
+        Glyph::get(ch)
+    }
+
+    fn advance(&self, glyph: Glyph) -> i32 {
+        glyph.width() + 1
+    }
+
+    fn spacing(&self) -> i32 {
+        1
+    }
+
+    fn line_height(&self) -> i32 {
+        HEIGHT
+    }
+
+    fn draw_glyph<D: graphics::Display>(
+        &self,
+        glyph: Glyph,
+        disp: &mut D,
+        pos: Vector,
+    ) -> Result<(), D::Error> {
+        // Each tile that the glyph overlaps is built up from all of the
+        // rectangles first, so that we only need to draw it once.
+        let bounds = glyph
+            .rects()
+            .fold(Rect::new(pos, pos), |bounds, rect| bounds.union(rect + pos));
+        for tile_pos in bounds.tiles() {
+            let area = Rect::new_sized(tile_pos * TILE_SIZE, Vector(TILE_SIZE, TILE_SIZE));
+            let mut bits = 0;
+            for rect in glyph.rects() {
+                if let Some(visible) = (rect + pos).intersection(area) {
+                    for p in visible.points() {
+                        bits |= tile_pixel_mask(p - area.start);
+                    }
+                }
+            }
+            if bits != 0 {
+                disp.draw_tile(bits, tile_pos)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphics::font::Font;
+    use graphics::framebuffer::FrameBuffer;
+
+    // Draws a glyph onto a small framebuffer with its top-left corner at
+    // the given position, and checks the result against a picture of the
+    // framebuffer with a '#' for each lit pixel.
+    fn check_drawn(glyph: Glyph, pos: Vector, want: &[&str; 8]) {
+        let mut buf = [0u8; 16];
+        let mut fb = FrameBuffer::new(Vector(16, 8), &mut buf[..]);
+        MiniFont.draw_glyph(glyph, &mut fb, pos).unwrap();
+        for (y, row) in want.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let p = Vector(x as i32, y as i32);
+                assert_eq!(fb.get_pixel(p), c == '#', "pixel {:?}", p);
+            }
+        }
+    }
+
+    #[test]
+    fn decode() {
+        match decode_command(0x35) {
+            Command::Rect { w: 5, h: 3 } => {}
+            _ => panic!("0x35 should be a 5x3 rectangle"),
+        }
+        match decode_command(0xf0) {
+            Command::Skip(15) => {}
+            _ => panic!("0xf0 should skip 15 rows"),
+        }
+    }
+
+    #[test]
+    fn rects() {
+        // The rectangles run down each column in turn, and a rectangle can
+        // cover more than one column.
+        let mut rects = Glyph::get('T').rects();
+        assert_eq!(rects.next(), Some(Rect::new4(0, 0, 5, 1)));
+        assert_eq!(rects.next(), Some(Rect::new4(2, 1, 3, 5)));
+        assert_eq!(rects.next(), None);
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(Glyph::get('A').width(), 5);
+        assert_eq!(Glyph::get('1').width(), 5);
+        assert_eq!(Glyph::get(',').width(), 2);
+        // Lowercase and accented letters are drawn as plain capitals, and
+        // other characters that the font doesn't have get the placeholder.
+        assert_eq!(Glyph::get('a').0, Glyph::get('A').0);
+        assert_eq!(Glyph::get('\u{e9}').0, Glyph::get('E').0);
+        assert_eq!(Glyph::get('\u{20ac}').0, Glyph::get('\u{7f}').0);
+    }
+
+    #[test]
+    fn draw_aligned() {
+        check_drawn(
+            Glyph::get('A'),
+            Vector(0, 0),
+            &[
+                ".###............",
+                "#...#...........",
+                "#####...........",
+                "#...#...........",
+                "#...#...........",
+                "................",
+                "................",
+                "................",
+            ],
+        );
+    }
+
+    #[test]
+    fn draw_unaligned() {
+        // The glyph straddles two rows and two columns of tiles.
+        check_drawn(
+            Glyph::get('M'),
+            Vector(3, 2),
+            &[
+                "................",
+                "................",
+                "...#...#........",
+                "...##.##........",
+                "...#.#.#........",
+                "...#...#........",
+                "...#...#........",
+                "................",
+            ],
+        );
+    }
+
+    #[test]
+    fn draw_wide_rect() {
+        // A single command can describe a rectangle far wider than any
+        // glyph in the font, and drawing it partly off the display must
+        // neither panic nor wrap around.
+        check_drawn(
+            Glyph(&[0x3f, 0x20]),
+            Vector(5, 6),
+            &[
+                "................",
+                "................",
+                "................",
+                "................",
+                "................",
+                "................",
+                ".....###########",
+                ".....###########",
+            ],
+        );
+    }
+}
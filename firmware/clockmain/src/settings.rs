@@ -0,0 +1,283 @@
+use crate::alarm::{self, Alarm};
+use crate::dimming::Dimming;
+use crate::faces::{DateFormat, FaceId, SecondsStyle};
+use crate::locale::Language;
+use crate::worldclock::{self, MAX_CITIES};
+
+/// The number of bytes that `Settings::encode` produces.
+pub const SIZE: usize = 32;
+
+// The first byte of the encoded settings, which lets us recognize storage
+// that has never had settings saved into it.
+const MAGIC: u8 = 0xc1;
+
+// Marks an unused slot in the encoded list of world clock cities.
+const NO_ZONE: u8 = 0xff;
+
+// Incremented whenever the encoding changes in a way that older firmware
+// could not understand. Settings with any other version are rejected, so
+// the clock falls back to the defaults.
+const VERSION: u8 = 1;
+
+/// The number of ticks that `App` waits after the settings last changed
+/// before saving them, which is five seconds.
+pub const SAVE_DELAY_TICKS: u8 = 10;
+
+/// The number of brightness levels the user can choose between, numbered
+/// from 1 for the dimmest.
+pub const BRIGHTNESS_LEVELS: u8 = 4;
+
+/// The user's choices that should be remembered while the clock is switched
+/// off.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub face: FaceId,
+    /// Whether to show the time as a 24-hour clock rather than a 12-hour
+    /// one.
+    pub hr24: bool,
+    /// The display brightness, from 1 up to `BRIGHTNESS_LEVELS`.
+    pub brightness: u8,
+    pub alarms: [Alarm; alarm::COUNT],
+    /// How long a snoozed alarm waits before ringing again, in minutes.
+    pub snooze_minutes: u8,
+    /// The position in `worldclock::ZONES` of the city the clock is in.
+    pub home_zone: u8,
+    /// The positions in `worldclock::ZONES` of the other cities whose times
+    /// the world clock face shows.
+    pub world_zones: [Option<u8>; MAX_CITIES],
+    /// Whether to replace the face with the screen saver at night.
+    pub saver: bool,
+    /// The hours at which the night starts and ends, for the screen saver
+    /// and for dimming.
+    pub night_start: u8,
+    pub night_end: u8,
+    pub dimming: Dimming,
+    /// How faces with room for only a short date write it.
+    pub date_format: DateFormat,
+    pub seconds: SecondsStyle,
+    /// The language that faces write the names of days and months in.
+    pub language: Language,
+}
+
+impl Settings {
+    /// Produces a compact representation of the settings for `Storage`.
+    pub fn encode(&self) -> [u8; SIZE] {
+        let mut raw = [0u8; SIZE];
+        raw[0] = MAGIC;
+        raw[1] = VERSION;
+        raw[2] = self.face.index();
+        raw[3] = self.hr24 as u8;
+        raw[4] = self.brightness;
+        for (alarm, raw) in self.alarms.iter().zip(raw[5..].chunks_mut(3)) {
+            raw[0] = alarm.hour;
+            raw[1] = alarm.minute;
+            raw[2] = alarm.days | ((alarm.enabled as u8) << 7);
+        }
+        raw[14] = self.snooze_minutes;
+        raw[15] = self.home_zone;
+        for (zone, raw) in self.world_zones.iter().zip(raw[16..].iter_mut()) {
+            *raw = zone.unwrap_or(NO_ZONE);
+        }
+        raw[19] = self.saver as u8;
+        raw[20] = self.night_start;
+        raw[21] = self.night_end;
+        raw[22] = self.dimming.index();
+        raw[23] = self.date_format.index();
+        raw[24] = self.seconds.index();
+        raw[25] = self.language.index();
+        raw[SIZE - 1] = checksum(&raw[..SIZE - 1]);
+        raw
+    }
+
+    /// Decodes settings previously produced by `encode`, returning `None`
+    /// if the given data doesn't contain valid settings.
+    pub fn decode(raw: &[u8]) -> Option<Self> {
+        if raw.len() < SIZE || raw[0] != MAGIC || raw[1] != VERSION {
+            return None;
+        }
+        if raw[SIZE - 1] != checksum(&raw[..SIZE - 1]) {
+            return None;
+        }
+        if raw[3] > 1 || raw[4] < 1 || raw[4] > BRIGHTNESS_LEVELS {
+            return None;
+        }
+        if raw[14] < 1 || raw[14] > alarm::MAX_SNOOZE_MINUTES {
+            return None;
+        }
+        if raw[15] as usize >= worldclock::ZONES.len() {
+            return None;
+        }
+        if raw[19] > 1 || raw[20] > 23 || raw[21] > 23 {
+            return None;
+        }
+
+        let mut alarms = [Alarm::default(); alarm::COUNT];
+        for (alarm, raw) in alarms.iter_mut().zip(raw[5..].chunks(3)) {
+            if raw[0] > 23 || raw[1] > 59 {
+                return None;
+            }
+            *alarm = Alarm {
+                enabled: raw[2] & 0x80 != 0,
+                hour: raw[0],
+                minute: raw[1],
+                days: raw[2] & Alarm::EVERY_DAY,
+            };
+        }
+        let mut world_zones = [None; MAX_CITIES];
+        for (zone, raw) in world_zones.iter_mut().zip(raw[16..].iter()) {
+            *zone = match *raw {
+                NO_ZONE => None,
+                idx if (idx as usize) < worldclock::ZONES.len() => Some(idx),
+                _ => return None,
+            };
+        }
+
+        Some(Self {
+            face: FaceId::from_index(raw[2])?,
+            hr24: raw[3] != 0,
+            brightness: raw[4],
+            alarms,
+            snooze_minutes: raw[14],
+            home_zone: raw[15],
+            world_zones,
+            saver: raw[19] != 0,
+            night_start: raw[20],
+            night_end: raw[21],
+            dimming: Dimming::from_index(raw[22])?,
+            date_format: DateFormat::from_index(raw[23])?,
+            seconds: SecondsStyle::from_index(raw[24])?,
+            language: Language::from_index(raw[25])?,
+        })
+    }
+}
+
+impl core::default::Default for Settings {
+    fn default() -> Self {
+        Self {
+            face: FaceId::Classic,
+            hr24: true,
+            brightness: BRIGHTNESS_LEVELS,
+            alarms: [Alarm::default(); alarm::COUNT],
+            snooze_minutes: alarm::DEFAULT_SNOOZE_MINUTES,
+            home_zone: worldclock::DEFAULT_HOME,
+            world_zones: worldclock::DEFAULT_CITIES,
+            saver: false,
+            night_start: 23,
+            night_end: 7,
+            dimming: Dimming::Off,
+            date_format: DateFormat::Ordinal,
+            seconds: SecondsStyle::Off,
+            language: Language::English,
+        }
+    }
+}
+
+/// Implemented by objects that can keep a small amount of data while the
+/// clock is switched off, so that `App` can remember its `Settings`.
+pub trait Storage {
+    type Error: core::fmt::Debug;
+
+    /// Reads previously-saved data into the given buffer, returning false
+    /// if nothing has been saved yet. The result may still be garbage if
+    /// the storage can't tell whether it was written, so callers must
+    /// validate it.
+    fn load(&mut self, buf: &mut [u8]) -> Result<bool, Self::Error>;
+
+    /// Replaces the saved data with the given data.
+    fn save(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// A `Storage` that never remembers anything, for platforms that have
+/// nowhere to keep settings.
+pub struct NoStorage;
+
+impl Storage for NoStorage {
+    type Error = core::convert::Infallible;
+
+    fn load(&mut self, _buf: &mut [u8]) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn save(&mut self, _data: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, v| sum.rotate_left(1) ^ *v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Settings with something other than the default for everything.
+    fn custom() -> Settings {
+        let mut alarms = [Alarm::default(); alarm::COUNT];
+        alarms[1] = Alarm {
+            enabled: true,
+            hour: 6,
+            minute: 45,
+            days: 0x1f,
+        };
+        Settings {
+            face: FaceId::WorldClock,
+            hr24: false,
+            brightness: 2,
+            alarms,
+            snooze_minutes: 5,
+            home_zone: 3,
+            world_zones: [Some(0), None, Some(7)],
+            saver: true,
+            night_start: 22,
+            night_end: 6,
+            dimming: Dimming::Night,
+            date_format: DateFormat::YearMonthDay,
+            seconds: SecondsStyle::Bar,
+            language: Language::French,
+        }
+    }
+
+    // Replaces the checksum at the end of the settings after the test has
+    // changed them.
+    fn fix_checksum(raw: &mut [u8]) {
+        raw[SIZE - 1] = checksum(&raw[..SIZE - 1]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let defaults = Settings::default();
+        assert_eq!(Settings::decode(&defaults.encode()), Some(defaults));
+        let custom = custom();
+        assert_eq!(Settings::decode(&custom.encode()), Some(custom));
+    }
+
+    #[test]
+    fn bad_checksum() {
+        let mut raw = custom().encode();
+        raw[20] ^= 1;
+        assert_eq!(Settings::decode(&raw), None);
+        let mut raw = custom().encode();
+        raw[SIZE - 1] ^= 0x80;
+        assert_eq!(Settings::decode(&raw), None);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(Settings::decode(&[0xff; SIZE]), None);
+        assert_eq!(Settings::decode(&[0; SIZE]), None);
+        assert_eq!(Settings::decode(&custom().encode()[..SIZE - 1]), None);
+
+        // Settings from newer firmware than ours.
+        let mut raw = custom().encode();
+        raw[1] = VERSION + 1;
+        fix_checksum(&mut raw);
+        assert_eq!(Settings::decode(&raw), None);
+
+        // A value out of range, with a correct checksum.
+        let mut raw = custom().encode();
+        raw[4] = BRIGHTNESS_LEVELS + 1;
+        fix_checksum(&mut raw);
+        assert_eq!(Settings::decode(&raw), None);
+    }
+}
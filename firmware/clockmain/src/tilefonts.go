@@ -296,21 +296,30 @@ func writeBlockFontDefs(font Font, tiles TileBlock, fontFn, mapFn string) error
 	// and thus the data in ROM must be accessed byte-wise only because half
 	// of the characters do not have their values aligned for larger accesses.
 	//
-	// There are 48 glyphs in our character set, so our total data for this
-	// font is a total of 48 * 15 bytes = 720 bytes.
+	// There are 103 glyphs in our character set, so our total data for this
+	// font is a total of 103 * 15 bytes = 1545 bytes.
+	//
+	// Codepoints above 0xa0 are ISO 8859-1, which matches the first 256
+	// Unicode codepoints. Capital letters fill the whole height of the
+	// font, leaving no room for accents, so only the lowercase accented
+	// letters have glyphs and clockmain draws the others without accents.
 
 	ranges := [][2]byte{
 		{' ', '!'},
 		{',', '.'},
 		{'0', ':'},
-		// NOTE: The question mark glyph is not encoded, since that allows us
-		// to keep this to a nice even 48 characters. We'll see about adding it
-		// later if we have a use-case for it.
+		{'?', '?'},
 		{'A', 'Z'},
-		{0x7b, 0x80}, // The ordinal ligatures and some other misc glyphs
+		{'a', 0x80},  // Lowercase, the ordinal ligatures and some other misc glyphs
+		{0xa1, 0xa1}, // Inverted exclamation mark
+		{0xbf, 0xbf}, // Inverted question mark
+		{0xdf, 0xe4}, // Sharp s and accented a
+		{0xe7, 0xef}, // Accented c, e and i
+		{0xf1, 0xf6}, // Accented n and o
+		{0xf9, 0xfd}, // Accented u and y
 	}
 
-	var chars [48]Character
+	var chars [103]Character
 	i := 0
 	for _, rng := range ranges {
 		_, err := mf.Write(rng[:])
@@ -322,7 +331,7 @@ func writeBlockFontDefs(font Font, tiles TileBlock, fontFn, mapFn string) error
 			i++
 		}
 	}
-	if i != 48 {
+	if i != 103 {
 		return fmt.Errorf("unexpected number of characters %d", i)
 	}
 
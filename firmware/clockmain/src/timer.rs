@@ -0,0 +1,424 @@
+//! A countdown timer and a stopwatch, which keep running in the background
+//! whichever face is showing.
+
+/// The rate at which platforms deliver `Event::Frame`, which the stopwatch
+/// counts to measure time more finely than the half-second tick.
+pub const FRAMES_PER_SECOND: u32 = 25;
+
+/// The number of lap times the stopwatch remembers.
+pub const MAX_LAPS: usize = 3;
+
+/// The number of frames for which the stopwatch shows a lap time after it's
+/// recorded, which is three seconds.
+pub const LAP_DISPLAY_FRAMES: u8 = 75;
+
+/// The longest countdown the user can set, in minutes.
+pub const MAX_COUNTDOWN_MINUTES: u16 = 99;
+
+/// The number of ticks the countdown flashes for after it expires if nobody
+/// dismisses it, which is one minute.
+pub const EXPIRED_TICKS: u16 = 120;
+
+/// A timer that counts down from a duration chosen by the user, a minute
+/// at a time, and then flashes until dismissed.
+///
+/// The countdown is driven by `Event::Tick`, so that it's as accurate as
+/// the clock itself.
+pub struct Countdown {
+    // Both in half-seconds, to match the tick.
+    duration: u16,
+    remaining: u16,
+    running: bool,
+    // The number of ticks since the countdown expired, if it has.
+    expired: Option<u16>,
+}
+
+impl Countdown {
+    pub fn new() -> Self {
+        Self {
+            duration: 5 * 60 * 2,
+            remaining: 5 * 60 * 2,
+            running: false,
+            expired: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expired.is_some()
+    }
+
+    /// Returns true if the countdown hasn't started since it was last set
+    /// or reset.
+    pub fn is_reset(&self) -> bool {
+        !self.running && self.remaining == self.duration
+    }
+
+    /// Returns the number of whole seconds left, rounding up so that the
+    /// display reaches zero only as the countdown expires.
+    pub fn remaining_seconds(&self) -> u16 {
+        self.remaining.div_ceil(2)
+    }
+
+    /// Starts the countdown if it's stopped, or pauses it if it's running.
+    pub fn toggle(&mut self) {
+        if self.remaining > 0 {
+            self.running = !self.running;
+        }
+    }
+
+    /// Lengthens or shortens the countdown by a minute, wrapping around at
+    /// the ends of its range. This also restarts the countdown from the
+    /// new duration, so it does nothing while the countdown is running.
+    pub fn adjust(&mut self, up: bool) {
+        if self.running {
+            return;
+        }
+        let minutes = self.duration / 120;
+        let minutes = if up {
+            minutes % MAX_COUNTDOWN_MINUTES + 1
+        } else {
+            (minutes + MAX_COUNTDOWN_MINUTES - 2) % MAX_COUNTDOWN_MINUTES + 1
+        };
+        self.duration = minutes * 120;
+        self.remaining = self.duration;
+    }
+
+    /// Stops the countdown, including any flashing, and rewinds it to its
+    /// full duration.
+    pub fn reset(&mut self) {
+        self.running = false;
+        self.expired = None;
+        self.remaining = self.duration;
+    }
+
+    /// Advances the countdown by one tick, returning true if it has just
+    /// expired.
+    pub fn tick(&mut self) -> bool {
+        if let Some(ticks) = self.expired {
+            if ticks + 1 < EXPIRED_TICKS {
+                self.expired = Some(ticks + 1);
+            } else {
+                self.reset();
+            }
+            return false;
+        }
+        if !self.running {
+            return false;
+        }
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.running = false;
+            self.expired = Some(0);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl core::default::Default for Countdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A stopwatch that can record lap times.
+///
+/// The stopwatch counts `Event::Frame` rather than ticks while it's on the
+/// display, so that it can show tenths of a second. Platforms only deliver
+/// frames while something is animating, so it counts ticks otherwise.
+pub struct Stopwatch {
+    frames: u32,
+    running: bool,
+    // Set when the last tick rounded half a second up to a whole number
+    // of frames, so that the next one rounds down.
+    extra_frame: bool,
+    // The elapsed time at the end of each of the most recent laps, with the
+    // most recent first.
+    splits: [u32; MAX_LAPS + 1],
+    laps: usize,
+    // The number of frames left to show the most recent lap for.
+    showing_lap: u8,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Self {
+            frames: 0,
+            running: false,
+            extra_frame: false,
+            splits: [0; MAX_LAPS + 1],
+            laps: 0,
+            showing_lap: 0,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Returns the total time measured so far, in frames.
+    pub fn elapsed(&self) -> u32 {
+        self.frames
+    }
+
+    /// Returns true if the stopwatch has just recorded a lap, in which case
+    /// it should show the time of that lap instead of the total for a
+    /// little while.
+    pub fn is_showing_lap(&self) -> bool {
+        self.showing_lap > 0
+    }
+
+    /// Returns the number of laps recorded since the last reset.
+    pub fn lap_count(&self) -> usize {
+        self.laps
+    }
+
+    /// Returns the duration in frames of a recent lap, where 0 is the most
+    /// recent, or `None` if that lap wasn't recorded or has been forgotten.
+    pub fn lap(&self, idx: usize) -> Option<u32> {
+        if idx >= MAX_LAPS || idx >= self.laps {
+            return None;
+        }
+        // The lap before the first one ends at zero.
+        let start = if idx + 1 < self.laps {
+            self.splits[idx + 1]
+        } else {
+            0
+        };
+        Some(self.splits[idx] - start)
+    }
+
+    pub fn toggle(&mut self) {
+        self.running = !self.running;
+    }
+
+    /// Records the end of a lap while the stopwatch is running, or resets
+    /// it to zero while it's stopped.
+    pub fn lap_or_reset(&mut self) {
+        if !self.running {
+            *self = Self::new();
+            return;
+        }
+        for i in (1..self.splits.len()).rev() {
+            self.splits[i] = self.splits[i - 1];
+        }
+        self.splits[0] = self.frames;
+        self.laps += 1;
+        self.showing_lap = LAP_DISPLAY_FRAMES;
+    }
+
+    /// Advances the stopwatch by one frame, if it's running.
+    pub fn frame(&mut self) {
+        self.showing_lap = self.showing_lap.saturating_sub(1);
+        if self.running {
+            self.frames = self.frames.saturating_add(1);
+        }
+    }
+
+    /// Advances the stopwatch by half a second, if it's running, for when
+    /// it isn't on the display to receive frames.
+    pub fn tick(&mut self) {
+        // Nobody saw the lap time, so there's no point showing it later.
+        self.showing_lap = 0;
+        if self.running {
+            // Every other tick rounds up, in case there's an odd number of
+            // frames in a second.
+            self.extra_frame = !self.extra_frame;
+            let frames = (FRAMES_PER_SECOND + self.extra_frame as u32) / 2;
+            self.frames = self.frames.saturating_add(frames);
+        }
+    }
+}
+
+impl core::default::Default for Stopwatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Splits a number of frames into minutes, seconds and tenths of a second.
+pub fn split_frames(frames: u32) -> (u32, u8, u8) {
+    // Splitting off the seconds first keeps this from overflowing for
+    // counts near the limit.
+    let seconds = frames / FRAMES_PER_SECOND;
+    let tenths = (frames % FRAMES_PER_SECOND) * 10 / FRAMES_PER_SECOND;
+    (seconds / 60, (seconds % 60) as u8, tenths as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns a countdown set to the given number of minutes.
+    fn countdown(minutes: u16) -> Countdown {
+        let mut countdown = Countdown::new();
+        while countdown.remaining_seconds() != minutes * 60 {
+            countdown.adjust(false);
+        }
+        countdown
+    }
+
+    #[test]
+    fn countdown_expires() {
+        let mut countdown = countdown(1);
+        assert!(countdown.is_reset());
+        countdown.toggle();
+        assert!(countdown.is_running());
+        assert!(!countdown.is_reset());
+        for _ in 0..119 {
+            assert!(!countdown.tick());
+        }
+        // The last half-second still shows as one second left.
+        assert_eq!(countdown.remaining_seconds(), 1);
+        assert!(countdown.tick());
+        assert!(countdown.is_expired());
+        assert!(!countdown.is_running());
+        assert_eq!(countdown.remaining_seconds(), 0);
+
+        // It flashes for a while and then resets itself.
+        for _ in 1..EXPIRED_TICKS {
+            assert!(!countdown.tick());
+            assert!(countdown.is_expired());
+        }
+        assert!(!countdown.tick());
+        assert!(!countdown.is_expired());
+        assert!(countdown.is_reset());
+        assert_eq!(countdown.remaining_seconds(), 60);
+    }
+
+    #[test]
+    fn countdown_pause_and_resume() {
+        let mut countdown = countdown(2);
+        countdown.toggle();
+        for _ in 0..10 {
+            countdown.tick();
+        }
+        assert_eq!(countdown.remaining_seconds(), 115);
+        countdown.toggle();
+        assert!(!countdown.is_running());
+        for _ in 0..10 {
+            countdown.tick();
+        }
+        assert_eq!(countdown.remaining_seconds(), 115);
+        assert!(!countdown.is_reset());
+        countdown.toggle();
+        countdown.tick();
+        countdown.tick();
+        assert_eq!(countdown.remaining_seconds(), 114);
+
+        // Resetting rewinds to the full duration.
+        countdown.reset();
+        assert!(countdown.is_reset());
+        assert_eq!(countdown.remaining_seconds(), 120);
+    }
+
+    #[test]
+    fn countdown_adjust() {
+        let mut countdown = countdown(1);
+        countdown.adjust(false);
+        assert_eq!(countdown.remaining_seconds(), MAX_COUNTDOWN_MINUTES * 60);
+        countdown.adjust(true);
+        assert_eq!(countdown.remaining_seconds(), 60);
+        countdown.adjust(true);
+        assert_eq!(countdown.remaining_seconds(), 120);
+
+        // Adjusting a paused countdown starts it again from the new
+        // duration, but a running one can't be adjusted.
+        countdown.toggle();
+        countdown.tick();
+        countdown.adjust(true);
+        assert_eq!(countdown.remaining_seconds(), 120);
+        countdown.toggle();
+        countdown.adjust(true);
+        assert_eq!(countdown.remaining_seconds(), 180);
+        assert!(countdown.is_reset());
+    }
+
+    #[test]
+    fn stopwatch_counts_while_running() {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.frame();
+        assert_eq!(stopwatch.elapsed(), 0);
+        stopwatch.toggle();
+        for _ in 0..30 {
+            stopwatch.frame();
+        }
+        stopwatch.toggle();
+        stopwatch.frame();
+        assert_eq!(stopwatch.elapsed(), 30);
+
+        // Resetting only works while stopped.
+        stopwatch.lap_or_reset();
+        assert_eq!(stopwatch.elapsed(), 0);
+        assert!(!stopwatch.is_running());
+    }
+
+    #[test]
+    fn stopwatch_counts_ticks() {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.tick();
+        assert_eq!(stopwatch.elapsed(), 0);
+        stopwatch.toggle();
+        for _ in 0..5 {
+            stopwatch.tick();
+        }
+        // Two and a half seconds, give or take half a frame.
+        assert_eq!(stopwatch.elapsed(), 63);
+        stopwatch.tick();
+        assert_eq!(stopwatch.elapsed(), 3 * FRAMES_PER_SECOND);
+
+        stopwatch.lap_or_reset();
+        assert!(stopwatch.is_showing_lap());
+        stopwatch.tick();
+        assert!(!stopwatch.is_showing_lap());
+    }
+
+    #[test]
+    fn stopwatch_laps() {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.toggle();
+        for lap in 1..=4 {
+            for _ in 0..lap * 10 {
+                stopwatch.frame();
+            }
+            stopwatch.lap_or_reset();
+        }
+        assert_eq!(stopwatch.elapsed(), 100);
+        assert_eq!(stopwatch.lap_count(), 4);
+        assert!(stopwatch.is_showing_lap());
+        // Only the most recent laps are remembered.
+        assert_eq!(stopwatch.lap(0), Some(40));
+        assert_eq!(stopwatch.lap(1), Some(30));
+        assert_eq!(stopwatch.lap(2), Some(20));
+        assert_eq!(stopwatch.lap(MAX_LAPS), None);
+
+        for _ in 0..LAP_DISPLAY_FRAMES {
+            stopwatch.frame();
+        }
+        assert!(!stopwatch.is_showing_lap());
+        assert_eq!(stopwatch.lap(0), Some(40));
+    }
+
+    #[test]
+    fn stopwatch_rollover() {
+        let fps = FRAMES_PER_SECOND;
+        assert_eq!(split_frames(0), (0, 0, 0));
+        assert_eq!(split_frames(fps * 60 - 1), (0, 59, 9));
+        assert_eq!(split_frames(fps * 60), (1, 0, 0));
+        assert_eq!(split_frames(fps * 3600 + 5), (60, 0, 2));
+        assert_eq!(split_frames(u32::MAX), (2_863_311, 31, 8));
+
+        // The count stops at its limit rather than going back to zero.
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.frames = u32::MAX - 1;
+        stopwatch.toggle();
+        stopwatch.frame();
+        stopwatch.frame();
+        assert_eq!(stopwatch.elapsed(), u32::MAX);
+    }
+}
@@ -0,0 +1,391 @@
+//! Works out the time in other cities from the time shown by the clock.
+//!
+//! The clock chip keeps local time in the user's home city, so we convert
+//! that to UTC using the home city's offset and then back into the time of
+//! each other city.
+//!
+//! Each city also has a location, so that the clock can work out when the
+//! sun rises and sets at home.
+
+use bcdtime::DateTime;
+use suntimes::{Location, SunTimes};
+
+/// The number of other cities that the user can choose to show.
+pub const MAX_CITIES: usize = 3;
+
+/// The rules for when a city observes daylight saving time, each of which
+/// moves its clocks forward by an hour.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dst {
+    None,
+    /// From 2am local time on the second Sunday of March until 2am on the
+    /// first Sunday of November.
+    UnitedStates,
+    /// From 1am UTC on the last Sunday of March until 1am UTC on the last
+    /// Sunday of October.
+    Europe,
+    /// From 2am local time on the first Sunday of October until 3am on the
+    /// first Sunday of April.
+    Australia,
+}
+
+/// A city whose time the clock knows how to show.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Zone {
+    /// A short name for the city, to show beside its time.
+    pub label: &'static str,
+    /// The difference from UTC to standard time in the city, in minutes.
+    pub offset: i16,
+    pub dst: Dst,
+    /// Where the city is, for working out when the sun rises and sets.
+    pub location: Location,
+}
+
+/// The cities the user can choose from, ordered by their offsets. The
+/// locations are in tenths of a degree north and east.
+pub const ZONES: [Zone; 18] = [
+    zone("HNL", -10 * 60, Dst::None, 213, -1579),
+    zone("LAX", -8 * 60, Dst::UnitedStates, 341, -1182),
+    zone("DEN", -7 * 60, Dst::UnitedStates, 397, -1050),
+    zone("CHI", -6 * 60, Dst::UnitedStates, 419, -876),
+    zone("NYC", -5 * 60, Dst::UnitedStates, 407, -740),
+    zone("SAO", -3 * 60, Dst::None, -236, -466),
+    zone("UTC", 0, Dst::None, 515, 0),
+    zone("LON", 0, Dst::Europe, 515, -1),
+    zone("PAR", 60, Dst::Europe, 489, 24),
+    zone("BER", 60, Dst::Europe, 525, 134),
+    zone("ATH", 2 * 60, Dst::Europe, 380, 237),
+    zone("MOW", 3 * 60, Dst::None, 558, 376),
+    zone("DXB", 4 * 60, Dst::None, 252, 553),
+    zone("DEL", 5 * 60 + 30, Dst::None, 286, 772),
+    zone("SGP", 8 * 60, Dst::None, 14, 1038),
+    zone("TYO", 9 * 60, Dst::None, 357, 1397),
+    zone("SYD", 10 * 60, Dst::Australia, -339, 1512),
+    zone("AKL", 12 * 60, Dst::None, -368, 1748),
+];
+
+/// The position in `ZONES` of the city that the clock assumes it's in
+/// until the user chooses otherwise.
+pub const DEFAULT_HOME: u8 = 7;
+
+/// The positions in `ZONES` of the other cities shown until the user
+/// chooses otherwise.
+pub const DEFAULT_CITIES: [Option<u8>; MAX_CITIES] = [Some(4), Some(1), Some(15)];
+
+const fn zone(label: &'static str, offset: i16, dst: Dst, lat: i16, lon: i16) -> Zone {
+    Zone {
+        label: label,
+        offset: offset,
+        dst: dst,
+        location: Location::new(lat, lon),
+    }
+}
+
+/// The time in another city, as returned by `convert`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ZoneTime {
+    pub hour: u8,
+    pub minute: u8,
+    /// The difference between the date in the other city and the date at
+    /// home, which is -1, 0 or 1.
+    pub days: i8,
+}
+
+/// Returns the time in the given city when the clock, which is in the
+/// given home city, shows the given time.
+pub fn convert(dt: &DateTime, home: &Zone, city: &Zone) -> ZoneTime {
+    let local = Moment::from_datetime(dt);
+    let utc = local.add_minutes(-home_offset(dt, home));
+    let there = utc.add_minutes(utc_offset(city, utc));
+    let days = match there.day().cmp(&local.day()) {
+        core::cmp::Ordering::Less => -1,
+        core::cmp::Ordering::Equal => 0,
+        core::cmp::Ordering::Greater => 1,
+    };
+    ZoneTime {
+        hour: (there.minutes / 60) as u8,
+        minute: (there.minutes % 60) as u8,
+        days: days,
+    }
+}
+
+/// Returns the difference between UTC and the time in the home city, in
+/// minutes, when the clock shows the given time.
+pub fn home_offset(dt: &DateTime, home: &Zone) -> i16 {
+    // Near the moment when the home city's clocks change, there's an hour
+    // that either happens twice or not at all, so in that hour we just
+    // accept whichever answer this gives.
+    let standard = Moment::from_datetime(dt).add_minutes(-home.offset);
+    if is_dst(home, standard.add_minutes(-60)) {
+        home.offset + 60
+    } else {
+        home.offset
+    }
+}
+
+/// Returns when the sun rises and sets in the home city on the date the
+/// clock shows, in minutes after local midnight.
+pub fn sun_times(dt: &DateTime, home: &Zone) -> SunTimes {
+    let offset = home_offset(dt, home);
+    let to_local = |minutes: i16| (minutes + offset + 24 * 60) % (24 * 60);
+    match suntimes::sun_times(dt, &home.location) {
+        SunTimes::Rises { sunrise, sunset } => SunTimes::Rises {
+            sunrise: to_local(sunrise),
+            sunset: to_local(sunset),
+        },
+        other => other,
+    }
+}
+
+/// Returns the difference between UTC and the time in the given city at
+/// the given moment, in minutes.
+fn utc_offset(zone: &Zone, utc: Moment) -> i16 {
+    if is_dst(zone, utc) {
+        zone.offset + 60
+    } else {
+        zone.offset
+    }
+}
+
+fn is_dst(zone: &Zone, utc: Moment) -> bool {
+    let year = utc.year;
+    // The US and Australian rules are in terms of local standard time.
+    let local = utc.add_minutes(zone.offset);
+    match zone.dst {
+        Dst::None => false,
+        Dst::UnitedStates => {
+            let start = Moment::new(year, 3, nth_sunday(year, 3, 2), 2 * 60);
+            let end = Moment::new(year, 11, nth_sunday(year, 11, 1), 60);
+            local >= start && local < end
+        }
+        Dst::Europe => {
+            let start = Moment::new(year, 3, last_sunday(year, 3), 60);
+            let end = Moment::new(year, 10, last_sunday(year, 10), 60);
+            utc >= start && utc < end
+        }
+        Dst::Australia => {
+            // Daylight saving time spans the new year in the southern
+            // hemisphere.
+            let end = Moment::new(year, 4, nth_sunday(year, 4, 1), 2 * 60);
+            let start = Moment::new(year, 10, nth_sunday(year, 10, 1), 2 * 60);
+            local < end || local >= start
+        }
+    }
+}
+
+// Returns the date of the nth Sunday of the given month, counting from 1.
+fn nth_sunday(year: u16, month: u8, n: u8) -> u8 {
+    let first = bcdtime::weekday(year, month, 1);
+    1 + (6 + 7 - first) % 7 + (n - 1) * 7
+}
+
+fn last_sunday(year: u16, month: u8) -> u8 {
+    let last = bcdtime::days_in_month(year, month);
+    last - (bcdtime::weekday(year, month, last) + 1) % 7
+}
+
+// A date and a time of day, which is just enough to convert between time
+// zones. The fields are ordered so that comparing moments compares them
+// chronologically.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Moment {
+    year: u16,
+    month: u8,
+    date: u8,
+    // Minutes since midnight.
+    minutes: i16,
+}
+
+impl Moment {
+    fn new(year: u16, month: u8, date: u8, minutes: i16) -> Self {
+        Self {
+            year: year,
+            month: month,
+            date: date,
+            minutes: minutes,
+        }
+    }
+
+    fn from_datetime(dt: &DateTime) -> Self {
+        let year: u8 = dt.year.into();
+        let hour: u8 = dt.hour.into();
+        let minute: u8 = dt.minute.into();
+        Self::new(
+            2000 + year as u16,
+            dt.month.into(),
+            dt.date.into(),
+            hour as i16 * 60 + minute as i16,
+        )
+    }
+
+    fn day(&self) -> (u16, u8, u8) {
+        (self.year, self.month, self.date)
+    }
+
+    // Returns the moment the given number of minutes later, which may be
+    // negative to go back in time. Time zone offsets are always less than
+    // a day, so this only needs to move to the next or previous day.
+    fn add_minutes(self, delta: i16) -> Self {
+        let mut ret = self;
+        ret.minutes += delta;
+        if ret.minutes >= 24 * 60 {
+            ret.minutes -= 24 * 60;
+            ret.date += 1;
+            if ret.date > bcdtime::days_in_month(ret.year, ret.month) {
+                ret.date = 1;
+                ret.month += 1;
+                if ret.month > 12 {
+                    ret.month = 1;
+                    ret.year += 1;
+                }
+            }
+        } else if ret.minutes < 0 {
+            ret.minutes += 24 * 60;
+            ret.date -= 1;
+            if ret.date < 1 {
+                if ret.month > 1 {
+                    ret.month -= 1;
+                } else {
+                    ret.month = 12;
+                    ret.year -= 1;
+                }
+                ret.date = bcdtime::days_in_month(ret.year, ret.month);
+            }
+        }
+        ret
+    }
+}
+
+/// Writes the given offset from UTC in a form like "UTC+5:30" into the
+/// buffer, returning the part of the buffer that was written.
+pub fn format_offset(offset: i16, buf: &mut [char; 9]) -> &[char] {
+    buf[..3].copy_from_slice(&['U', 'T', 'C']);
+    if offset == 0 {
+        return &buf[..3];
+    }
+    buf[3] = if offset < 0 { '-' } else { '+' };
+    let hours = (offset.abs() / 60) as u8;
+    let minutes = (offset.abs() % 60) as u8;
+    let mut len = 4;
+    if hours >= 10 {
+        buf[len] = (b'0' + hours / 10) as char;
+        len += 1;
+    }
+    buf[len] = (b'0' + hours % 10) as char;
+    len += 1;
+    if minutes != 0 {
+        buf[len] = ':';
+        buf[len + 1] = (b'0' + minutes / 10) as char;
+        buf[len + 2] = (b'0' + minutes % 10) as char;
+        len += 3;
+    }
+    &buf[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bcdtime::BCD;
+
+    fn city(label: &str) -> &'static Zone {
+        ZONES.iter().find(|z| z.label == label).unwrap()
+    }
+
+    // Returns a time on the given day of 2026. The day of the week doesn't
+    // matter to time zones.
+    fn at(month: u8, date: u8, hour: u8, minute: u8) -> DateTime {
+        DateTime {
+            second: BCD::from(0),
+            minute: BCD::from(minute),
+            hour: BCD::from(hour),
+            day: 0,
+            date: BCD::from(date),
+            month: BCD::from(month),
+            year: BCD::from(26),
+            hr24: true,
+        }
+    }
+
+    fn utc(month: u8, date: u8, hour: i16, minute: i16) -> Moment {
+        Moment::new(2026, month, date, hour * 60 + minute)
+    }
+
+    #[test]
+    fn switchover_dates() {
+        assert_eq!(last_sunday(2026, 3), 29);
+        assert_eq!(last_sunday(2026, 10), 25);
+        assert_eq!(nth_sunday(2026, 3, 2), 8);
+        assert_eq!(nth_sunday(2026, 11, 1), 1);
+        // A month that starts on a Sunday.
+        assert_eq!(nth_sunday(2026, 2, 1), 1);
+        assert_eq!(last_sunday(2026, 5), 31);
+    }
+
+    #[test]
+    fn europe_switches_at_one_utc() {
+        for &label in &["LON", "PAR", "ATH"] {
+            let zone = city(label);
+            assert!(!is_dst(zone, utc(3, 29, 0, 59)), "{}", label);
+            assert!(is_dst(zone, utc(3, 29, 1, 0)), "{}", label);
+            assert!(is_dst(zone, utc(10, 25, 0, 59)), "{}", label);
+            assert!(!is_dst(zone, utc(10, 25, 1, 0)), "{}", label);
+        }
+    }
+
+    #[test]
+    fn united_states_switches_at_two_local() {
+        // 2:00 standard time in New York is 7:00 UTC, and in Los Angeles
+        // it's 10:00 UTC.
+        let nyc = city("NYC");
+        assert!(!is_dst(nyc, utc(3, 8, 6, 59)));
+        assert!(is_dst(nyc, utc(3, 8, 7, 0)));
+        let lax = city("LAX");
+        assert!(!is_dst(lax, utc(3, 8, 9, 59)));
+        assert!(is_dst(lax, utc(3, 8, 10, 0)));
+
+        // Clocks go back at 2:00 daylight time, which is 1:00 standard.
+        assert!(is_dst(nyc, utc(11, 1, 5, 59)));
+        assert!(!is_dst(nyc, utc(11, 1, 6, 0)));
+        assert!(is_dst(lax, utc(11, 1, 8, 59)));
+        assert!(!is_dst(lax, utc(11, 1, 9, 0)));
+    }
+
+    #[test]
+    fn home_offset_around_switchovers() {
+        let lon = city("LON");
+        assert_eq!(home_offset(&at(3, 29, 0, 59), lon), 0);
+        assert_eq!(home_offset(&at(3, 29, 2, 0), lon), 60);
+        assert_eq!(home_offset(&at(10, 25, 0, 59), lon), 60);
+        assert_eq!(home_offset(&at(10, 25, 2, 0), lon), 0);
+
+        let nyc = city("NYC");
+        assert_eq!(home_offset(&at(3, 8, 1, 59), nyc), -5 * 60);
+        assert_eq!(home_offset(&at(3, 8, 3, 0), nyc), -4 * 60);
+        assert_eq!(home_offset(&at(11, 1, 0, 59), nyc), -4 * 60);
+        assert_eq!(home_offset(&at(11, 1, 2, 0), nyc), -5 * 60);
+    }
+
+    #[test]
+    fn convert_between_switchovers() {
+        let lon = city("LON");
+        let nyc = city("NYC");
+        let time = |hour, minute, days| ZoneTime {
+            hour: hour,
+            minute: minute,
+            days: days,
+        };
+        // The US changes its clocks a few weeks before Europe in the
+        // spring, and a week after it in the autumn.
+        assert_eq!(convert(&at(3, 1, 12, 0), lon, nyc), time(7, 0, 0));
+        assert_eq!(convert(&at(3, 20, 12, 0), lon, nyc), time(8, 0, 0));
+        assert_eq!(convert(&at(3, 30, 12, 0), lon, nyc), time(7, 0, 0));
+        assert_eq!(convert(&at(10, 28, 12, 0), lon, nyc), time(8, 0, 0));
+        assert_eq!(convert(&at(11, 2, 12, 0), lon, nyc), time(7, 0, 0));
+
+        // Sydney's daylight saving time runs from October to April.
+        let syd = city("SYD");
+        assert_eq!(convert(&at(1, 15, 12, 0), lon, syd), time(23, 0, 0));
+        assert_eq!(convert(&at(7, 15, 12, 0), lon, syd), time(21, 0, 0));
+        assert_eq!(convert(&at(3, 1, 20, 0), nyc, syd), time(12, 0, 1));
+    }
+}
@@ -6,6 +6,11 @@ use bcdtime::{BCD, DateTime};
 
 const I2C_ADDR: u8 = 0b1101000;
 
+// The alarm 2 interrupt enable bit in the control register, and the alarm
+// 2 flag in the status register.
+const A2IE: u8 = 0b00000010;
+const A2F: u8 = 0b00000010;
+
 pub struct DS3231<I2C>
 where
     I2C: i2c::WriteRead + i2c::Write,
@@ -41,6 +46,63 @@ where
         } as u8;
         self.i2c.write(I2C_ADDR, &[0x0eu8, raw]).map_err(Error::w)
     }
+
+    /// Programs alarm 2 to go off at the given time, or disables it if
+    /// `alarm` is `None`.
+    ///
+    /// The alarm drives the INT/SQW pin only while the square wave output
+    /// is disabled, but `check_alarm2` can detect it either way.
+    pub fn set_alarm2(&mut self, alarm: Option<AlarmTime>) -> Result<(), Error<WRErr, WErr>> {
+        let mut regs: [u8; 2] = [0u8; 2]; // control and status
+        self.i2c.write_read(I2C_ADDR, &[0x0eu8], &mut regs[..]).map_err(Error::wr)?;
+
+        let mut control = regs[0] & !A2IE;
+        if let Some(alarm) = alarm {
+            // Leaving the mask bits clear makes the alarm match on the
+            // minute, hour and day together, and the DY/DT bit selects the
+            // day of the week rather than the date. The chip counts the
+            // days of the week from one.
+            let raw = [
+                0x0bu8,
+                alarm.minute.raw(),
+                alarm.hour.raw(),
+                0b01000000 | (alarm.day + 1),
+            ];
+            self.i2c.write(I2C_ADDR, &raw[..]).map_err(Error::w)?;
+            control |= A2IE;
+        }
+
+        // We also clear any earlier alarm, so that check_alarm2 will report
+        // only the new one.
+        self.i2c.write(I2C_ADDR, &[0x0eu8, control, regs[1] & !A2F]).map_err(Error::w)
+    }
+
+    /// Returns true if alarm 2 has gone off since the last call, and resets
+    /// it so that it can go off again.
+    pub fn check_alarm2(&mut self) -> Result<bool, Error<WRErr, WErr>> {
+        let mut status: [u8; 1] = [0u8; 1];
+        self.i2c.write_read(I2C_ADDR, &[0x0fu8], &mut status[..]).map_err(Error::wr)?;
+        if status[0] & A2F == 0 {
+            return Ok(false);
+        }
+        // Writing ones to the flags leaves them unchanged, so this clears
+        // only the alarm 2 flag.
+        self.i2c.write(I2C_ADDR, &[0x0fu8, status[0] & !A2F]).map_err(Error::w)?;
+        Ok(true)
+    }
+
+    /// Reads the chip's built-in temperature sensor, returning the result
+    /// in quarters of a degree Celsius. The chip only updates the reading
+    /// every 64 seconds.
+    pub fn temperature(&mut self) -> Result<i16, Error<WRErr, WErr>> {
+        let mut result: [u8; 2] = [0u8; 2];
+        self.i2c.write_read(I2C_ADDR, &[0x11u8], &mut result[..]).map_err(Error::wr)?;
+
+        // The first register is the whole number of degrees as a two's
+        // complement integer, and the top two bits of the second are the
+        // fractional part.
+        Ok(((result[0] as i8 as i16) << 2) | (result[1] >> 6) as i16)
+    }
 }
 
 impl<I2C, WRErr, WErr> bcdtime::Read for DS3231<I2C>
@@ -71,7 +133,9 @@ where
                     BCD::from_raw(result[2] & 0b00011111)
                 }
             },
-            day: result[3],
+            // The chip counts the days of the week from one, and we
+            // count from zero.
+            day: (result[3] & 0b00000111).saturating_sub(1),
             date: BCD::from_raw(result[4]),
             month: {
                 // The month part has the century flag packed into it too,
@@ -102,7 +166,7 @@ where
         data[0] = dt.second.raw();
         data[1] = dt.minute.raw();
         data[2] = dt.hour.raw();
-        data[3] = dt.day;
+        data[3] = dt.day + 1;
         data[4] = dt.date.raw();
         data[5] = dt.month.raw(); // NOTE: This always sets century back to zero
         data[6] = dt.year.raw();
@@ -144,6 +208,15 @@ where
     }
 }
 
+/// A time for alarm 2, which can go off once a week at a particular minute.
+pub struct AlarmTime {
+    /// The day of the week, numbered as for `DateTime::day`.
+    pub day: u8,
+    /// The hour, using a 24-hour clock.
+    pub hour: BCD,
+    pub minute: BCD,
+}
+
 pub enum SquareWaveFrequency {
     Disabled,
     Freq1Hz,
@@ -151,3 +224,89 @@ pub enum SquareWaveFrequency {
     Freq4_096kHz,
     Freq8_192kHz,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for the chip's registers, as seen over I2C.
+    struct FakeChip {
+        regs: [u8; 0x13],
+    }
+
+    impl i2c::Write for FakeChip {
+        type Error = ();
+
+        fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), ()> {
+            assert_eq!(addr, I2C_ADDR);
+            let start = bytes[0] as usize;
+            self.regs[start..start + bytes.len() - 1].copy_from_slice(&bytes[1..]);
+            Ok(())
+        }
+    }
+
+    impl i2c::WriteRead for FakeChip {
+        type Error = ();
+
+        fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), ()> {
+            assert_eq!(addr, I2C_ADDR);
+            let start = bytes[0] as usize;
+            buffer.copy_from_slice(&self.regs[start..start + buffer.len()]);
+            Ok(())
+        }
+    }
+
+    fn chip(control: u8, status: u8) -> DS3231<FakeChip> {
+        let mut regs = [0u8; 0x13];
+        regs[0x0e] = control;
+        regs[0x0f] = status;
+        DS3231::new(FakeChip { regs: regs })
+    }
+
+    #[test]
+    fn set_alarm2() {
+        // The square wave settings in the control register must survive,
+        // and the alarm 1 flag in the status register must stay set.
+        let mut rtc = chip(0b00011100, 0b10001011);
+        let alarm = AlarmTime {
+            day: 6,
+            hour: BCD::from(21),
+            minute: BCD::from(45),
+        };
+        rtc.set_alarm2(Some(alarm)).unwrap();
+        let regs = &rtc.i2c.regs;
+        // Sunday is day 7 to the chip, and bit 6 selects the day of the
+        // week rather than the date.
+        assert_eq!(regs[0x0b..0x0e], [0x45, 0x21, 0b01000111]);
+        assert_eq!(regs[0x0e], 0b00011110);
+        assert_eq!(regs[0x0f], 0b10001001);
+
+        // Monday is day 1.
+        let alarm = AlarmTime {
+            day: 0,
+            hour: BCD::from(7),
+            minute: BCD::from(0),
+        };
+        rtc.set_alarm2(Some(alarm)).unwrap();
+        assert_eq!(rtc.i2c.regs[0x0b..0x0e], [0x00, 0x07, 0b01000001]);
+    }
+
+    #[test]
+    fn disable_alarm2() {
+        let mut rtc = chip(0b00011110, 0b00000010);
+        rtc.i2c.regs[0x0b] = 0x30;
+        rtc.set_alarm2(None).unwrap();
+        assert_eq!(rtc.i2c.regs[0x0e], 0b00011100);
+        assert_eq!(rtc.i2c.regs[0x0f], 0);
+        // The alarm registers are left alone.
+        assert_eq!(rtc.i2c.regs[0x0b], 0x30);
+    }
+
+    #[test]
+    fn check_alarm2() {
+        let mut rtc = chip(0, 0b10000011);
+        assert!(rtc.check_alarm2().unwrap());
+        assert_eq!(rtc.i2c.regs[0x0f], 0b10000001);
+        assert!(!rtc.check_alarm2().unwrap());
+    }
+}
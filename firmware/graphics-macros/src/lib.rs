@@ -0,0 +1,232 @@
+//! Procedural macros for embedding images into firmware as
+//! `graphics::bitmap::Bitmap` values.
+//!
+//! These are re-exported from the `graphics` crate, so callers should use
+//! `graphics::include_pbm!` and `graphics::include_xbm!` rather than
+//! depending on this crate directly.
+
+extern crate proc_macro;
+
+use proc_macro::{TokenStream, TokenTree};
+use std::path::PathBuf;
+
+/// Embeds a PBM image file as a `graphics::bitmap::Bitmap`.
+///
+/// Both the binary ("P4") and plain ("P1") variants are supported. The path
+/// is interpreted relative to the directory containing the calling crate's
+/// `Cargo.toml`.
+#[proc_macro]
+pub fn include_pbm(input: TokenStream) -> TokenStream {
+    expand(input, parse_pbm)
+}
+
+/// Embeds an XBM image file as a `graphics::bitmap::Bitmap`.
+///
+/// The path is interpreted relative to the directory containing the calling
+/// crate's `Cargo.toml`.
+#[proc_macro]
+pub fn include_xbm(input: TokenStream) -> TokenStream {
+    expand(input, parse_xbm)
+}
+
+/// A decoded image, with rows packed most-significant-bit first and padded
+/// to a whole number of bytes.
+struct Image {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+fn expand(input: TokenStream, parse: fn(&[u8]) -> Result<Image, String>) -> TokenStream {
+    let result = path_arg(input).and_then(|path| {
+        let raw = std::fs::read(&path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        let img =
+            parse(&raw).map_err(|err| format!("invalid image {}: {}", path.display(), err))?;
+        Ok((path, img))
+    });
+
+    let src = match result {
+        Ok((path, img)) => {
+            let bytes: Vec<String> = img.data.iter().map(|b| format!("{:#04x}", b)).collect();
+            // The include_bytes! call is never used at runtime, but it makes
+            // the compiler aware that the result depends on the image file
+            // so that the calling crate is rebuilt when the file changes.
+            format!(
+                "{{ const _SOURCE: &[u8] = include_bytes!({:?}); \
+                 ::graphics::bitmap::Bitmap::new({}, {}, &[{}]) }}",
+                path.display().to_string(),
+                img.width,
+                img.height,
+                bytes.join(", "),
+            )
+        }
+        Err(msg) => format!("compile_error!({:?})", msg),
+    };
+    src.parse().unwrap()
+}
+
+fn path_arg(input: TokenStream) -> Result<PathBuf, String> {
+    let mut tokens = input.into_iter();
+    let lit = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(lit)), None) => lit.to_string(),
+        _ => return Err("expected a single string literal giving a file path".into()),
+    };
+    if lit.len() < 2 || !lit.starts_with('"') || !lit.ends_with('"') {
+        return Err("expected a single string literal giving a file path".into());
+    }
+    let rel = &lit[1..lit.len() - 1];
+
+    let base = std::env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| String::from("CARGO_MANIFEST_DIR is not set"))?;
+    Ok(PathBuf::from(base).join(rel))
+}
+
+fn parse_pbm(raw: &[u8]) -> Result<Image, String> {
+    let mut r = Reader { raw, pos: 0 };
+    let magic = r.word();
+    let binary = match magic {
+        b"P4" => true,
+        b"P1" => false,
+        _ => return Err("not a P1 or P4 PBM file".into()),
+    };
+    let width = r.number()?;
+    let height = r.number()?;
+    let stride = width.div_ceil(8);
+
+    if binary {
+        // Exactly one whitespace character separates the header from the
+        // raster, which is already in the layout we want.
+        let start = r.pos + 1;
+        let end = start + stride * height;
+        if raw.len() < end {
+            return Err("raster data is truncated".into());
+        }
+        return Ok(Image {
+            width,
+            height,
+            data: raw[start..end].to_vec(),
+        });
+    }
+
+    let mut data = vec![0u8; stride * height];
+    for y in 0..height {
+        for x in 0..width {
+            r.skip_space();
+            match r.next() {
+                Some(b'1') => data[y * stride + x / 8] |= 0x80 >> (x % 8),
+                Some(b'0') => {}
+                _ => return Err("raster data is truncated or malformed".into()),
+            }
+        }
+    }
+    Ok(Image {
+        width,
+        height,
+        data,
+    })
+}
+
+fn parse_xbm(raw: &[u8]) -> Result<Image, String> {
+    let src = std::str::from_utf8(raw).map_err(|_| String::from("not a text file"))?;
+
+    let mut width = None;
+    let mut height = None;
+    for line in src.lines() {
+        let mut words = line.split_whitespace();
+        if words.next() != Some("#define") {
+            continue;
+        }
+        let name = words.next().unwrap_or("");
+        let value = words.next().and_then(|v| v.parse::<usize>().ok());
+        if name.ends_with("_width") {
+            width = value;
+        } else if name.ends_with("_height") {
+            height = value;
+        }
+    }
+    let width = width.ok_or_else(|| String::from("missing _width definition"))?;
+    let height = height.ok_or_else(|| String::from("missing _height definition"))?;
+
+    let body_start = src
+        .find('{')
+        .ok_or_else(|| String::from("missing pixel data"))?;
+    let body_end = src
+        .find('}')
+        .ok_or_else(|| String::from("missing pixel data"))?;
+    if body_end < body_start {
+        return Err("missing pixel data".into());
+    }
+
+    let mut data = Vec::new();
+    for item in src[body_start + 1..body_end].split(',') {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        let hex = item.trim_start_matches("0x").trim_start_matches("0X");
+        let v = u8::from_str_radix(hex, 16).map_err(|_| format!("invalid byte {:?}", item))?;
+        // XBM packs its pixels least-significant-bit first.
+        data.push(v.reverse_bits());
+    }
+
+    let stride = width.div_ceil(8);
+    if data.len() < stride * height {
+        return Err("pixel data is truncated".into());
+    }
+    data.truncate(stride * height);
+    Ok(Image {
+        width,
+        height,
+        data,
+    })
+}
+
+struct Reader<'a> {
+    raw: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn next(&mut self) -> Option<u8> {
+        let ret = self.raw.get(self.pos).cloned();
+        self.pos += 1;
+        ret
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(c) = self.raw.get(self.pos).cloned() {
+            if c == b'#' {
+                while let Some(c) = self.next() {
+                    if c == b'\n' {
+                        break;
+                    }
+                }
+            } else if c.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn word(&mut self) -> &'a [u8] {
+        self.skip_space();
+        let start = self.pos;
+        while let Some(c) = self.raw.get(self.pos) {
+            if c.is_ascii_whitespace() || *c == b'#' {
+                break;
+            }
+            self.pos += 1;
+        }
+        &self.raw[start..self.pos]
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let w = self.word();
+        std::str::from_utf8(w)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| String::from("invalid header"))
+    }
+}
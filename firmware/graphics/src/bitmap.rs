@@ -0,0 +1,193 @@
+use crate::vector::{Rect, Vector};
+use crate::{Display, TILE_SIZE};
+
+/// A monochrome image with one bit per pixel.
+///
+/// Each row is packed most-significant-bit first and padded to a whole
+/// number of bytes, which is the same layout used by the raster of a binary
+/// ("P4") PBM file. Use `include_pbm!` or `include_xbm!` to embed an image
+/// file into the program at compile time.
+#[derive(Copy, Clone, Debug)]
+pub struct Bitmap<'a> {
+    width: i32,
+    height: i32,
+    data: &'a [u8],
+}
+
+impl<'a> Bitmap<'a> {
+    /// Creates a bitmap from packed pixel data.
+    ///
+    /// `data` should contain at least `height` rows of `(width + 7) / 8`
+    /// bytes each. Any pixels that fall beyond the end of a shorter buffer
+    /// read as unset.
+    pub const fn new(width: i32, height: i32, data: &'a [u8]) -> Self {
+        Self {
+            width: width,
+            height: height,
+            data: data,
+        }
+    }
+
+    /// Interprets the given bytes as a binary ("P4") PBM file, returning
+    /// `None` if they are not in that format.
+    ///
+    /// The result borrows the raster data from `raw` rather than copying it.
+    pub fn from_pbm(raw: &'a [u8]) -> Option<Self> {
+        let mut pos = 0;
+        if pbm_word(raw, &mut pos)? != b"P4" {
+            return None;
+        }
+        let width = pbm_number(raw, &mut pos)?;
+        let height = pbm_number(raw, &mut pos)?;
+
+        // Exactly one whitespace character separates the header from the
+        // raster data.
+        let start = pos + 1;
+        let end = start + stride(width) * height as usize;
+        if raw.len() < end {
+            return None;
+        }
+        Some(Self::new(width, height, &raw[start..end]))
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn size(&self) -> Vector {
+        Vector(self.width, self.height)
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::new(Vector(0, 0), self.size())
+    }
+
+    /// Returns the number of bytes used to represent each row of pixels.
+    pub fn stride(&self) -> usize {
+        stride(self.width)
+    }
+
+    /// Returns the packed pixel data.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns whether the pixel at the given position is set. Positions
+    /// outside of the bitmap are always unset.
+    pub fn get_pixel(&self, p: Vector) -> bool {
+        if !p.inside(self.bounds()) {
+            return false;
+        }
+        let i = p.1 as usize * self.stride() + (p.0 / 8) as usize;
+        match self.data.get(i) {
+            Some(b) => (b & (0x80 >> (p.0 % 8))) != 0,
+            None => false,
+        }
+    }
+
+    /// Returns the raw tile data for the 4x4 block of pixels whose top-left
+    /// corner is at `origin`, in the format used by `Tile::raw_pixel_data`.
+    pub fn tile_bits(&self, origin: Vector) -> u16 {
+        let mut ret = 0;
+        for y in 0..TILE_SIZE {
+            for x in 0..TILE_SIZE {
+                if self.get_pixel(origin + Vector(x, y)) {
+                    ret |= tile_pixel_mask(Vector(x, y));
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// Selects how a bitmap is combined with the pixels already present at its
+/// destination.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlitMode {
+    /// Set pixels are drawn and unset pixels leave the destination as-is.
+    Transparent,
+    /// The destination is replaced by the bitmap, including unset pixels.
+    Opaque,
+    /// Set pixels invert the destination and unset pixels leave it as-is.
+    Xor,
+}
+
+impl BlitMode {
+    /// Combines a destination pixel with a source pixel.
+    pub fn apply(self, dst: bool, src: bool) -> bool {
+        match self {
+            BlitMode::Transparent => dst | src,
+            BlitMode::Opaque => src,
+            BlitMode::Xor => dst ^ src,
+        }
+    }
+}
+
+/// Draws a bitmap with its top-left corner at the given pixel position.
+///
+/// Generic displays can only be drawn to a whole tile at a time and cannot
+/// report what was already drawn, so this draws each tile that contains at
+/// least one set pixel of the bitmap. How the unset pixels in those tiles
+/// combine with earlier drawing depends on the display; draw into a
+/// `FrameBuffer` instead to choose a `BlitMode`.
+pub fn draw_bitmap<D: Display>(disp: &mut D, bmp: &Bitmap, pos: Vector) -> Result<(), D::Error> {
+    let area = Rect::new_sized(pos, bmp.size());
+    for tile_pos in area.tiles() {
+        let bits = bmp.tile_bits(tile_pos * TILE_SIZE - pos);
+        if bits != 0 {
+            disp.draw_tile(bits, tile_pos)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the bit representing the given pixel within raw tile data.
+pub fn tile_pixel_mask(p: Vector) -> u16 {
+    1 << (p.1 * TILE_SIZE + (TILE_SIZE - 1 - p.0))
+}
+
+/// Returns the number of bytes in each row of pixel data for an image of
+/// the given width, in the layout used by `Bitmap` and `FrameBuffer`.
+pub(crate) fn stride(width: i32) -> usize {
+    (width as usize).div_ceil(8)
+}
+
+fn pbm_word<'a>(raw: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    // Skip whitespace and comments before the word.
+    while let Some(c) = raw.get(*pos) {
+        if *c == b'#' {
+            while raw.get(*pos).map_or(false, |c| *c != b'\n') {
+                *pos += 1;
+            }
+        } else if c.is_ascii_whitespace() {
+            *pos += 1;
+        } else {
+            break;
+        }
+    }
+
+    let start = *pos;
+    while raw.get(*pos).map_or(false, |c| !c.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+    if start == *pos {
+        None
+    } else {
+        Some(&raw[start..*pos])
+    }
+}
+
+fn pbm_number(raw: &[u8], pos: &mut usize) -> Option<i32> {
+    let mut ret: i32 = 0;
+    for c in pbm_word(raw, pos)? {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        ret = ret.checked_mul(10)?.checked_add((c - b'0') as i32)?;
+    }
+    Some(ret)
+}
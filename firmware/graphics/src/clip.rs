@@ -0,0 +1,121 @@
+use crate::bitmap::tile_pixel_mask;
+use crate::vector::{Rect, Vector};
+use crate::{Display, Tile, TILE_SIZE};
+
+/// Wraps another display so that only pixels inside a given rectangle are
+/// drawn.
+///
+/// The clip rectangle is measured in pixels and need not be aligned to
+/// tiles. Tiles that straddle its edge have their outside pixels turned
+/// off before they are drawn, so on displays that draw whole tiles at once
+/// those pixels will be erased rather than left alone.
+pub struct ClipDisplay<'a, D: Display> {
+    inner: &'a mut D,
+    clip: Rect,
+}
+
+impl<'a, D: Display> ClipDisplay<'a, D> {
+    pub fn new(inner: &'a mut D, clip: Rect) -> Self {
+        Self {
+            inner: inner,
+            clip: clip.normalized(),
+        }
+    }
+
+    pub fn clip(&self) -> Rect {
+        self.clip
+    }
+}
+
+impl<'a, D: Display> Display for ClipDisplay<'a, D> {
+    type Error = D::Error;
+    type P = D::P;
+    const OFF: D::P = D::OFF;
+    const ON: D::P = D::ON;
+
+    fn size(&self) -> Vector {
+        self.inner.size()
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        self.inner.flip()
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.inner.clear()
+    }
+
+    fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        let area = Rect::new_sized(pos * TILE_SIZE, Vector(TILE_SIZE, TILE_SIZE));
+        let visible = match area.intersection(self.clip) {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+        if visible == area {
+            return self.inner.draw_tile(tile, pos);
+        }
+
+        let mut mask = 0;
+        for p in visible.points() {
+            mask |= tile_pixel_mask(p - area.start);
+        }
+        // Even a tile whose visible part is blank must be drawn, because
+        // it may be erasing something.
+        self.inner.draw_tile(tile.raw_pixel_data() & mask, pos)
+    }
+
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        self.inner.set_shift(shift)
+    }
+
+    fn set_brightness(&mut self, level: u8) -> Result<(), Self::Error> {
+        self.inner.set_brightness(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::FrameBuffer;
+
+    #[test]
+    fn erase_half_visible_tile() {
+        let mut buf = [0u8; 8];
+        let mut fb = FrameBuffer::new(Vector(8, 8), &mut buf[..]);
+        fb.draw_tile(0xffffu16, Vector(0, 0)).unwrap();
+
+        // Only the left half of the tile is inside the clip, and drawing a
+        // blank tile there must still turn those pixels off.
+        let mut clipped = ClipDisplay::new(&mut fb, Rect::new4(0, 0, 2, 8));
+        clipped.draw_tile(0u16, Vector(0, 0)).unwrap();
+        for p in Rect::new4(0, 0, 2, 4).points() {
+            assert!(!fb.get_pixel(p), "pixel {:?}", p);
+        }
+    }
+
+    #[test]
+    fn clip_edges() {
+        let mut buf = [0u8; 8];
+        let mut fb = FrameBuffer::new(Vector(8, 8), &mut buf[..]);
+        let mut clipped = ClipDisplay::new(&mut fb, Rect::new4(1, 1, 6, 3));
+        clipped.draw_tile(0xffffu16, Vector(0, 0)).unwrap();
+        clipped.draw_tile(0xffffu16, Vector(1, 0)).unwrap();
+        clipped.draw_tile(0xffffu16, Vector(0, 1)).unwrap();
+        for p in Rect::new4(0, 0, 8, 8).points() {
+            let want = p.inside(Rect::new4(1, 1, 6, 3));
+            assert_eq!(fb.get_pixel(p), want, "pixel {:?}", p);
+        }
+    }
+
+    #[test]
+    fn forwards_shift() {
+        let mut buf = [0u8; 8];
+        let mut fb = FrameBuffer::new(Vector(8, 8), &mut buf[..]);
+        let mut clipped = ClipDisplay::new(&mut fb, Rect::new4(0, 0, 8, 8));
+        let dot = tile_pixel_mask(Vector(0, 0));
+        clipped.set_shift(Vector(1, 1)).unwrap();
+        clipped.draw_tile(dot, Vector(0, 0)).unwrap();
+        assert!(fb.get_pixel(Vector(1, 1)));
+        assert!(!fb.get_pixel(Vector(0, 0)));
+    }
+}
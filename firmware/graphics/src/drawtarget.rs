@@ -0,0 +1,146 @@
+//! Adapters for rendering `embedded-graphics` scenes onto our displays.
+//!
+//! This module is available only when the "embedded-graphics" feature is
+//! enabled.
+//!
+//! Our `Display` trait can only draw whole tiles and has no way to read back
+//! what was drawn, while `DrawTarget` deals in individual pixels. The
+//! adapters therefore render into a `FrameBuffer` and then copy complete
+//! tiles onto the real display.
+
+use crate::framebuffer::FrameBuffer;
+use crate::vector::{Rect, Vector};
+use crate::Display;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Pixel;
+
+impl<B> DrawTarget for FrameBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(p, color) in pixels {
+            self.set_pixel(Vector(p.x, p.y), color.is_on());
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_rect(rect_from_eg(area), color.is_on());
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let bounds = self.bounds();
+        self.fill_rect(bounds, color.is_on());
+        Ok(())
+    }
+}
+
+impl<B> OriginDimensions for FrameBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn size(&self) -> Size {
+        let size = Display::size(self);
+        Size::new(size.0 as u32, size.1 as u32)
+    }
+}
+
+/// Wraps any `Display` to make it usable as an embedded-graphics
+/// `DrawTarget`.
+///
+/// Drawing operations update an internal framebuffer stored in `buf`, which
+/// must be at least `FrameBuffer::buffer_size` bytes for the display's size.
+/// Call `flush` to present the result on the wrapped display.
+pub struct DrawTargetDisplay<D, B> {
+    disp: D,
+    fb: FrameBuffer<B>,
+}
+
+impl<D, B> DrawTargetDisplay<D, B>
+where
+    D: Display,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    pub fn new(disp: D, buf: B) -> Self {
+        let size = disp.size();
+        Self {
+            disp: disp,
+            fb: FrameBuffer::new(size, buf),
+        }
+    }
+
+    /// Clears the wrapped display, copies the framebuffer onto it, and
+    /// then flips it so that the result becomes visible.
+    ///
+    /// The framebuffer is left intact, so the caller can either continue
+    /// drawing on top of the previous frame or clear it first.
+    pub fn flush(&mut self) -> Result<(), D::Error> {
+        self.disp.clear()?;
+        self.fb.draw_to(&mut self.disp, Vector(0, 0))?;
+        self.disp.flip()
+    }
+
+    pub fn display(&mut self) -> &mut D {
+        &mut self.disp
+    }
+
+    pub fn framebuffer(&mut self) -> &mut FrameBuffer<B> {
+        &mut self.fb
+    }
+
+    pub fn release(self) -> (D, B) {
+        (self.disp, self.fb.release())
+    }
+}
+
+impl<D, B> DrawTarget for DrawTargetDisplay<D, B>
+where
+    D: Display,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.fb.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fb.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        DrawTarget::clear(&mut self.fb, color)
+    }
+}
+
+impl<D, B> OriginDimensions for DrawTargetDisplay<D, B>
+where
+    D: Display,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn size(&self) -> Size {
+        OriginDimensions::size(&self.fb)
+    }
+}
+
+fn rect_from_eg(r: &Rectangle) -> Rect {
+    Rect::new_sized(
+        Vector(r.top_left.x, r.top_left.y),
+        Vector(r.size.width as i32, r.size.height as i32),
+    )
+}
@@ -0,0 +1,101 @@
+use crate::merge::MergeDisplay;
+use crate::vector::Vector;
+use crate::Display;
+
+/// Implemented by fonts that can draw text onto a `Display`.
+///
+/// All measurements and positions are in pixels. Fonts whose glyphs are
+/// built from whole tiles will generally draw fastest when positioned on
+/// tile boundaries, but must also support drawing at any pixel position.
+pub trait Font {
+    /// A handle for a single glyph in the font, cheap to copy.
+    type Glyph: Copy;
+
+    /// Returns the glyph for the given character. Fonts return some sort of
+    /// placeholder glyph for characters they don't support.
+    fn glyph(&self, ch: char) -> Self::Glyph;
+
+    /// Returns the horizontal distance from the left edge of the given glyph
+    /// to the left edge of the glyph that follows it, including the spacing
+    /// between them.
+    fn advance(&self, glyph: Self::Glyph) -> i32;
+
+    /// Returns the width of the blank space at the right of every advance
+    /// that separates adjacent glyphs. This is excluded when measuring the
+    /// width of a whole string.
+    fn spacing(&self) -> i32 {
+        0
+    }
+
+    /// Returns the height of a line of text.
+    fn line_height(&self) -> i32;
+
+    /// Draws a single glyph with its top-left corner at the given position.
+    fn draw_glyph<D: Display>(
+        &self,
+        glyph: Self::Glyph,
+        disp: &mut D,
+        pos: Vector,
+    ) -> Result<(), D::Error>;
+}
+
+/// Returns the width in pixels of the given characters when drawn in the
+/// given font.
+pub fn measure_chars<F, I>(font: &F, chars: I) -> i32
+where
+    F: Font,
+    I: IntoIterator<Item = char>,
+{
+    let mut w = 0;
+    for ch in chars {
+        w += font.advance(font.glyph(ch));
+    }
+    if w > 0 {
+        w - font.spacing()
+    } else {
+        0
+    }
+}
+
+/// Returns the width in pixels of the given string when drawn in the given
+/// font.
+pub fn measure<F: Font>(font: &F, text: &str) -> i32 {
+    measure_chars(font, text.chars())
+}
+
+/// Draws the given characters in a single line with the top-left corner of
+/// the first at the given pixel position, returning the width of the
+/// result as `measure_chars` would.
+///
+/// Adjacent glyphs that share a tile are combined, so this is safe to use
+/// with fonts whose glyphs are not tile-aligned.
+pub fn draw_chars<F, I, D>(font: &F, chars: I, disp: &mut D, pos: Vector) -> Result<i32, D::Error>
+where
+    F: Font,
+    I: IntoIterator<Item = char>,
+    D: Display,
+{
+    let mut merged = MergeDisplay::new(disp, Vector(0, 0));
+    let mut x = 0;
+    for ch in chars {
+        let glyph = font.glyph(ch);
+        font.draw_glyph(glyph, &mut merged, pos + Vector(x, 0))?;
+        x += font.advance(glyph);
+    }
+    if x > 0 {
+        Ok(x - font.spacing())
+    } else {
+        Ok(0)
+    }
+}
+
+/// Draws the given string in a single line with its top-left corner at the
+/// given pixel position, returning its width as `measure` would.
+pub fn draw_text<F: Font, D: Display>(
+    font: &F,
+    text: &str,
+    disp: &mut D,
+    pos: Vector,
+) -> Result<i32, D::Error> {
+    draw_chars(font, text.chars(), disp, pos)
+}
@@ -0,0 +1,152 @@
+use crate::bitmap::{stride, tile_pixel_mask, Bitmap, BlitMode};
+use crate::vector::{Rect, Vector};
+use crate::{Display, Tile, TILE_SIZE};
+
+/// An in-memory monochrome image that can be drawn into at the level of
+/// individual pixels, and then copied onto a real display.
+///
+/// The pixel data uses the same layout as `Bitmap`, and is stored in a
+/// caller-provided buffer so that the caller can decide whether it lives
+/// in a static, on the stack, or on the heap. Use `FrameBuffer::buffer_size`
+/// to find how large the buffer must be.
+///
+/// `FrameBuffer` is itself a `Display`, so any drawing code written for
+/// displays can also render into it.
+pub struct FrameBuffer<B> {
+    size: Vector,
+    buf: B,
+    // Added to the position of each tile drawn through `Display`.
+    shift: Vector,
+}
+
+impl<B> FrameBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Creates a framebuffer of the given size in pixels.
+    ///
+    /// Panics if `buf` is smaller than `buffer_size(size)`.
+    pub fn new(size: Vector, buf: B) -> Self {
+        assert!(buf.as_ref().len() >= Self::buffer_size(size));
+        Self {
+            size: size,
+            buf: buf,
+            shift: Vector(0, 0),
+        }
+    }
+
+    /// Returns the number of bytes of storage required for a framebuffer of
+    /// the given size in pixels.
+    pub fn buffer_size(size: Vector) -> usize {
+        stride(size.0) * size.1 as usize
+    }
+
+    pub fn release(self) -> B {
+        self.buf
+    }
+
+    /// Returns a bitmap view of the current contents of the framebuffer.
+    pub fn as_bitmap(&self) -> Bitmap<'_> {
+        Bitmap::new(self.size.0, self.size.1, self.buf.as_ref())
+    }
+
+    pub fn get_pixel(&self, p: Vector) -> bool {
+        self.as_bitmap().get_pixel(p)
+    }
+
+    /// Sets or unsets the pixel at the given position. Positions outside of
+    /// the framebuffer are ignored.
+    pub fn set_pixel(&mut self, p: Vector, on: bool) {
+        if !p.inside(self.bounds()) {
+            return;
+        }
+        let i = p.1 as usize * stride(self.size.0) + (p.0 / 8) as usize;
+        let mask = 0x80 >> (p.0 % 8);
+        let data = self.buf.as_mut();
+        if on {
+            data[i] |= mask;
+        } else {
+            data[i] &= !mask;
+        }
+    }
+
+    /// Sets or unsets all of the pixels in the given rectangle, clipped to
+    /// the bounds of the framebuffer.
+    pub fn fill_rect(&mut self, rect: Rect, on: bool) {
+        for p in rect.clip(self.bounds()).points() {
+            self.set_pixel(p, on);
+        }
+    }
+
+    /// Combines a bitmap into the framebuffer with its top-left corner at
+    /// the given pixel position.
+    pub fn blit(&mut self, bmp: &Bitmap, pos: Vector, mode: BlitMode) {
+        let area = Rect::new_sized(pos, bmp.size()).clip(self.bounds());
+        for p in area.points() {
+            let src = bmp.get_pixel(p - pos);
+            let dst = self.get_pixel(p);
+            self.set_pixel(p, mode.apply(dst, src));
+        }
+    }
+
+    /// Draws the framebuffer contents onto another display, with the
+    /// framebuffer's top-left corner at the given tile position.
+    ///
+    /// Only tiles with at least one pixel set are drawn, so the destination
+    /// should usually be cleared first.
+    pub fn draw_to<D: Display>(&self, disp: &mut D, pos: Vector) -> Result<(), D::Error> {
+        let bmp = self.as_bitmap();
+        for tile_pos in self.bounds().tiles() {
+            let bits = bmp.tile_bits(tile_pos * TILE_SIZE);
+            if bits != 0 {
+                disp.draw_tile(bits, tile_pos + pos)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B> Display for FrameBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Error = core::convert::Infallible;
+    type P = bool;
+    const ON: bool = true;
+    const OFF: bool = false;
+
+    fn size(&self) -> Vector {
+        self.size
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        for b in self.buf.as_mut().iter_mut() {
+            *b = 0;
+        }
+        Ok(())
+    }
+
+    fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        if !pos.inside(self.tile_bounds()) {
+            return Ok(());
+        }
+        let raw = tile.raw_pixel_data();
+        let origin = pos * TILE_SIZE + self.shift;
+        for y in 0..TILE_SIZE {
+            for x in 0..TILE_SIZE {
+                let on = raw & tile_pixel_mask(Vector(x, y)) != 0;
+                self.set_pixel(origin + Vector(x, y), on);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        self.shift = shift;
+        Ok(())
+    }
+}
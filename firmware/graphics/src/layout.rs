@@ -0,0 +1,116 @@
+//! Helpers for positioning text within a rectangle, so that screen layouts
+//! can be described in terms of areas rather than hand-calculated
+//! positions.
+
+use crate::clip::ClipDisplay;
+use crate::font::{self, Font};
+use crate::vector::{Rect, Vector};
+use crate::Display;
+
+/// The text used to mark where characters were removed by
+/// `Overflow::Ellipsis`.
+pub const ELLIPSIS: &str = "...";
+
+/// Horizontal alignment of an item within a rectangle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    /// Returns the x coordinate at which an item of the given width should
+    /// start in order to be aligned within the given rectangle.
+    ///
+    /// Items that are wider than the rectangle overflow it on the side
+    /// opposite the alignment, or equally on both sides when centered.
+    pub fn x(self, width: i32, within: Rect) -> i32 {
+        let r = within.normalized();
+        match self {
+            Align::Left => r.start.0,
+            Align::Center => r.start.0 + (r.width() - width) / 2,
+            Align::Right => r.end.0 - width,
+        }
+    }
+}
+
+/// Determines what happens to text that is too wide for its rectangle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Draw the text in full, extending outside of the rectangle.
+    Visible,
+    /// Discard any pixels that fall outside of the rectangle.
+    Clip,
+    /// Remove characters from the end of the text until it fits along with
+    /// `ELLIPSIS`, which is drawn in their place.
+    Ellipsis,
+}
+
+/// Draws the given characters in a single line aligned within the given
+/// pixel rectangle, returning the area that the text occupies.
+///
+/// The text is positioned against the top edge of the rectangle. Its height
+/// is ignored except when clipping.
+pub fn draw_chars_in<F, I, D>(
+    font: &F,
+    chars: I,
+    disp: &mut D,
+    rect: Rect,
+    align: Align,
+    overflow: Overflow,
+) -> Result<Rect, D::Error>
+where
+    F: Font,
+    I: IntoIterator<Item = char>,
+    I::IntoIter: Clone,
+    D: Display,
+{
+    let rect = rect.normalized();
+    let chars = chars.into_iter();
+    let width = font::measure_chars(font, chars.clone());
+
+    if width <= rect.width() || overflow == Overflow::Visible {
+        let pos = Vector(align.x(width, rect), rect.start.1);
+        font::draw_chars(font, chars, disp, pos)?;
+        return Ok(Rect::new_sized(pos, Vector(width, font.line_height())));
+    }
+
+    let mut disp = ClipDisplay::new(disp, rect);
+    if overflow == Overflow::Clip {
+        let pos = Vector(align.x(width, rect), rect.start.1);
+        font::draw_chars(font, chars, &mut disp, pos)?;
+        return Ok(Rect::new_sized(pos, Vector(width, font.line_height())).clip(rect));
+    }
+
+    // For an ellipsis we keep as many whole characters as will fit in front
+    // of it. If not even the ellipsis fits alone then it'll be clipped.
+    let tail = font::measure(font, ELLIPSIS);
+    let mut keep = 0;
+    let mut x = 0;
+    for ch in chars.clone() {
+        x += font.advance(font.glyph(ch));
+        if x + tail > rect.width() {
+            break;
+        }
+        keep += 1;
+    }
+    let shortened = chars.take(keep).chain(ELLIPSIS.chars());
+    let width = font::measure_chars(font, shortened.clone());
+    let pos = Vector(align.x(width, rect), rect.start.1);
+    font::draw_chars(font, shortened, &mut disp, pos)?;
+    Ok(Rect::new_sized(pos, Vector(width, font.line_height())).clip(rect))
+}
+
+/// Draws the given string in a single line aligned within the given pixel
+/// rectangle, as with `draw_chars_in`.
+pub fn draw_text_in<F: Font, D: Display>(
+    font: &F,
+    text: &str,
+    disp: &mut D,
+    rect: Rect,
+    align: Align,
+    overflow: Overflow,
+) -> Result<Rect, D::Error> {
+    draw_chars_in(font, text.chars(), disp, rect, align, overflow)
+}
@@ -1,9 +1,25 @@
 #![no_std]
 
+pub mod bitmap;
+pub mod clip;
+#[cfg(feature = "embedded-graphics")]
+pub mod drawtarget;
+pub mod font;
+pub mod framebuffer;
+pub mod layout;
+pub mod marquee;
+pub mod merge;
+pub mod shapes;
+pub mod transform;
 pub mod vector;
 
+pub use graphics_macros::{include_pbm, include_xbm};
+
 use vector::{Rect, Vector};
 
+/// The width and height of a `Tile`, in pixels.
+pub const TILE_SIZE: i32 = 4;
+
 pub trait Display {
     type Error: core::fmt::Debug;
     type P;
@@ -13,9 +29,56 @@ pub trait Display {
     fn size(&self) -> Vector;
     fn flip(&mut self) -> Result<(), Self::Error>;
     fn clear(&mut self) -> Result<(), Self::Error>;
+
+    /// Draws a tile at the given position, measured in tiles.
+    ///
+    /// Implementations must silently discard tiles that fall outside of
+    /// `tile_bounds`, so that callers can draw partially off-screen without
+    /// checking positions themselves.
     fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error>;
+
+    /// Moves everything drawn afterwards by the given number of pixels,
+    /// which should be only a few, so that the pixels of an unchanging
+    /// image take turns being lit. This slows down burn-in on OLED panels.
+    ///
+    /// Parts of tiles shifted off the edges of the display are discarded.
+    /// Displays that can only draw tiles on the tile grid ignore this,
+    /// which is the default, and others may only be able to shift in one
+    /// direction or by more than a pixel at a time.
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        let _ = shift;
+        Ok(())
+    }
+
+    /// Sets how brightly lit pixels glow, from 0 for the dimmest level at
+    /// which they are still visible up to 255 for the brightest.
+    ///
+    /// Displays whose brightness can't be controlled ignore this, which is
+    /// the default, and others may support fewer distinct levels.
+    fn set_brightness(&mut self, level: u8) -> Result<(), Self::Error> {
+        let _ = level;
+        Ok(())
+    }
+
+    /// Returns the area of the display in pixels.
+    fn bounds(&self) -> Rect {
+        Rect::new(Vector(0, 0), self.size())
+    }
+
+    /// Returns the area of the display measured in tiles, which is the
+    /// coordinate space used by `draw_tile`.
+    fn tile_bounds(&self) -> Rect {
+        Rect::new(Vector(0, 0), self.size() / TILE_SIZE)
+    }
 }
 
 pub trait Tile {
     fn raw_pixel_data(&self) -> u16;
 }
+
+impl Tile for u16 {
+    #[inline(always)]
+    fn raw_pixel_data(&self) -> u16 {
+        *self
+    }
+}
@@ -0,0 +1,244 @@
+use crate::clip::ClipDisplay;
+use crate::font::{self, Font};
+use crate::vector::{Rect, Vector};
+use crate::Display;
+
+/// A single line of text that scrolls horizontally within a rectangle when
+/// it is too wide to fit.
+///
+/// The marquee doesn't keep time itself. Instead, the caller calls `step`
+/// at a regular interval, such as on each timer tick, and each step moves
+/// the text by the configured speed. Text that fits within the rectangle
+/// is drawn left-aligned and never moves.
+pub struct Marquee<'a, F: Font> {
+    font: F,
+    text: &'a str,
+    rect: Rect,
+    width: i32,
+    speed: i32,
+    pause: u32,
+    looping: bool,
+    offset: i32,
+    wait: u32,
+}
+
+impl<'a, F: Font> Marquee<'a, F> {
+    /// Creates a marquee showing the given text within the given pixel
+    /// rectangle. By default it moves one pixel per step, pauses for ten
+    /// steps at each end and loops forever.
+    pub fn new(font: F, text: &'a str, rect: Rect) -> Self {
+        let width = font::measure(&font, text);
+        Self {
+            font: font,
+            text: text,
+            rect: rect.normalized(),
+            width: width,
+            speed: 1,
+            pause: 10,
+            looping: true,
+            offset: 0,
+            wait: 10,
+        }
+    }
+
+    /// Sets the number of pixels the text moves on each step.
+    pub fn with_speed(mut self, pixels: i32) -> Self {
+        self.speed = pixels.max(1);
+        self
+    }
+
+    /// Sets the number of steps for which the text stays still when it
+    /// reaches either end.
+    pub fn with_pause(mut self, steps: u32) -> Self {
+        self.pause = steps;
+        self.wait = steps;
+        self
+    }
+
+    /// Sets whether the text returns to the start after pausing at the end,
+    /// or stays at the end once it gets there.
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Replaces the text and restarts scrolling from the beginning.
+    pub fn set_text(&mut self, text: &'a str) {
+        self.text = text;
+        self.width = font::measure(&self.font, text);
+        self.reset();
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Moves the marquee to a different rectangle, restarting scrolling
+    /// from the beginning.
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect.normalized();
+        self.reset();
+    }
+
+    /// Returns the text to its starting position.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.wait = self.pause;
+    }
+
+    /// Returns true if the text is too wide to fit in the rectangle, and so
+    /// will scroll.
+    pub fn scrolls(&self) -> bool {
+        self.width > self.rect.width()
+    }
+
+    /// Returns true if the marquee has stopped at the end of the text,
+    /// which only happens when it is not looping.
+    pub fn is_finished(&self) -> bool {
+        !self.scrolls() || (!self.looping && self.offset == self.max_offset() && self.wait == 0)
+    }
+
+    /// Advances the animation by one step, returning true if the text moved
+    /// and so needs to be redrawn.
+    pub fn step(&mut self) -> bool {
+        if !self.scrolls() {
+            return false;
+        }
+        if self.wait > 0 {
+            self.wait -= 1;
+            return false;
+        }
+
+        let max = self.max_offset();
+        if self.offset >= max {
+            if !self.looping {
+                return false;
+            }
+            self.reset();
+            return true;
+        }
+
+        self.offset = (self.offset + self.speed).min(max);
+        if self.offset == max {
+            self.wait = self.pause;
+        }
+        true
+    }
+
+    /// Draws the text at its current position. Nothing is drawn outside of
+    /// the marquee's rectangle.
+    pub fn draw<D: Display>(&self, disp: &mut D) -> Result<(), D::Error> {
+        let mut disp = ClipDisplay::new(disp, self.rect);
+        let pos = self.rect.start - Vector(self.offset, 0);
+        font::draw_text(&self.font, self.text, &mut disp, pos)?;
+        Ok(())
+    }
+
+    fn max_offset(&self) -> i32 {
+        (self.width - self.rect.width()).max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::FrameBuffer;
+    use crate::TILE_SIZE;
+
+    // A font whose glyphs are all a single filled tile, with no gap
+    // between them.
+    struct Blocks;
+
+    impl Font for Blocks {
+        type Glyph = char;
+
+        fn glyph(&self, ch: char) -> char {
+            ch
+        }
+
+        fn advance(&self, _glyph: char) -> i32 {
+            TILE_SIZE
+        }
+
+        fn line_height(&self) -> i32 {
+            TILE_SIZE
+        }
+
+        fn draw_glyph<D: Display>(
+            &self,
+            _glyph: char,
+            disp: &mut D,
+            pos: Vector,
+        ) -> Result<(), D::Error> {
+            disp.draw_tile(0xffffu16, pos / TILE_SIZE)
+        }
+    }
+
+    // Steps the marquee the given number of times, returning the offset
+    // after each step.
+    fn offsets(marquee: &mut Marquee<Blocks>, steps: usize) -> [i32; 12] {
+        let mut offsets = [-1; 12];
+        for offset in offsets.iter_mut().take(steps) {
+            marquee.step();
+            *offset = marquee.offset;
+        }
+        offsets
+    }
+
+    #[test]
+    fn short_text_stays_still() {
+        let mut marquee = Marquee::new(Blocks, "ABCD", Rect::new4(0, 0, 16, 4));
+        assert!(!marquee.scrolls());
+        assert!(marquee.is_finished());
+        assert!(!marquee.step());
+    }
+
+    #[test]
+    fn scrolls_pauses_and_loops() {
+        // The text is eight pixels wider than the rectangle.
+        let rect = Rect::new4(0, 0, 16, 4);
+        let mut marquee = Marquee::new(Blocks, "ABCDEF", rect)
+            .with_speed(3)
+            .with_pause(2);
+        assert!(marquee.scrolls());
+        assert_eq!(
+            offsets(&mut marquee, 11),
+            [0, 0, 3, 6, 8, 8, 8, 0, 0, 0, 3, -1]
+        );
+        assert!(!marquee.is_finished());
+    }
+
+    #[test]
+    fn stops_at_end_without_looping() {
+        let rect = Rect::new4(0, 0, 16, 4);
+        let mut marquee = Marquee::new(Blocks, "ABCDEF", rect)
+            .with_speed(4)
+            .with_pause(1)
+            .with_looping(false);
+        assert_eq!(
+            offsets(&mut marquee, 6),
+            [0, 4, 8, 8, 8, 8, -1, -1, -1, -1, -1, -1]
+        );
+        assert!(marquee.is_finished());
+        assert!(!marquee.step());
+    }
+
+    #[test]
+    fn draws_only_inside_rect() {
+        let mut buf = [0u8; 16];
+        let mut fb = FrameBuffer::new(Vector(32, 4), &mut buf[..]);
+        let mut marquee = Marquee::new(Blocks, "ABCDEF", Rect::new4(8, 0, 24, 4))
+            .with_speed(TILE_SIZE)
+            .with_pause(0);
+        marquee.step();
+        marquee.draw(&mut fb).unwrap();
+        for x in 0..32 {
+            let want = (8..24).contains(&x);
+            assert_eq!(fb.get_pixel(Vector(x, 0)), want, "pixel {}", x);
+        }
+    }
+}
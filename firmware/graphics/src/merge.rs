@@ -0,0 +1,128 @@
+use crate::bitmap::tile_pixel_mask;
+use crate::vector::{floor_div, Vector};
+use crate::{Display, Tile, TILE_SIZE};
+
+/// The number of recently-drawn tiles that `MergeDisplay` remembers.
+const CACHE_SIZE: usize = 16;
+
+/// Wraps another display for the duration of a single drawing operation,
+/// so that tiles drawn more than once at the same position are combined
+/// rather than replacing one another, and so that drawing can be offset by
+/// a number of pixels that is not a multiple of the tile size.
+///
+/// Some displays overwrite all sixteen pixels of a tile whenever a tile is
+/// drawn, so without this any two shapes that share a tile would erase
+/// part of each other. This wrapper remembers the most recently drawn tiles
+/// and draws the union of each one with whatever was previously drawn at
+/// the same position. Only a small number of tiles are remembered, so
+/// drawing code should try to revisit tiles soon after first drawing them.
+pub struct MergeDisplay<'a, D: Display> {
+    inner: &'a mut D,
+    offset: Vector,
+    cache: [(Vector, u16); CACHE_SIZE],
+    cache_len: usize,
+    cache_next: usize,
+}
+
+impl<'a, D: Display> MergeDisplay<'a, D> {
+    /// Wraps the given display so that tile positions given to `draw_tile`
+    /// are offset by the given number of pixels.
+    pub fn new(inner: &'a mut D, offset: Vector) -> Self {
+        Self {
+            inner: inner,
+            offset: offset,
+            cache: [(Vector(0, 0), 0); CACHE_SIZE],
+            cache_len: 0,
+            cache_next: 0,
+        }
+    }
+
+    /// Draws the given raw tile data at a position measured in pixels,
+    /// relative to the wrapper's own offset, splitting it over as many as
+    /// four tiles on the underlying display if necessary.
+    pub fn draw_bits_at(&mut self, raw: u16, pos: Vector) -> Result<(), D::Error> {
+        if raw == 0 {
+            return Ok(());
+        }
+        let pos = pos + self.offset;
+        let tile = Vector(floor_div(pos.0, TILE_SIZE), floor_div(pos.1, TILE_SIZE));
+        let shift = pos - tile * TILE_SIZE;
+        if shift == Vector(0, 0) {
+            return self.merge(raw, tile);
+        }
+
+        for dy in 0..2 {
+            for dx in 0..2 {
+                let mut bits = 0;
+                for y in 0..TILE_SIZE {
+                    for x in 0..TILE_SIZE {
+                        let dst = Vector(x, y) + shift - Vector(dx, dy) * TILE_SIZE;
+                        if raw & tile_pixel_mask(Vector(x, y)) != 0
+                            && dst.0 >= 0
+                            && dst.1 >= 0
+                            && dst.0 < TILE_SIZE
+                            && dst.1 < TILE_SIZE
+                        {
+                            bits |= tile_pixel_mask(dst);
+                        }
+                    }
+                }
+                if bits != 0 {
+                    self.merge(bits, tile + Vector(dx, dy))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, raw: u16, pos: Vector) -> Result<(), D::Error> {
+        if !pos.inside(self.inner.tile_bounds()) {
+            return Ok(());
+        }
+
+        let mut bits = raw;
+        let mut found = false;
+        for entry in self.cache[..self.cache_len].iter_mut() {
+            if entry.0 == pos {
+                entry.1 |= raw;
+                bits = entry.1;
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            self.cache[self.cache_next] = (pos, raw);
+            self.cache_next = (self.cache_next + 1) % CACHE_SIZE;
+            if self.cache_len < CACHE_SIZE {
+                self.cache_len += 1;
+            }
+        }
+
+        self.inner.draw_tile(bits, pos)
+    }
+}
+
+impl<'a, D: Display> Display for MergeDisplay<'a, D> {
+    type Error = D::Error;
+    type P = D::P;
+    const OFF: D::P = D::OFF;
+    const ON: D::P = D::ON;
+
+    fn size(&self) -> Vector {
+        self.inner.size()
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        self.inner.flip()
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.cache_len = 0;
+        self.cache_next = 0;
+        self.inner.clear()
+    }
+
+    fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        self.draw_bits_at(tile.raw_pixel_data(), pos * TILE_SIZE)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::bitmap::tile_pixel_mask;
+use crate::vector::{Rect, Vector};
+use crate::{Display, TILE_SIZE};
+
+/// Lights every pixel in the given rectangle, which is measured in pixels
+/// and need not be aligned to tiles.
+///
+/// Tiles that straddle the edge of the rectangle are drawn with only the
+/// pixels inside it lit, so as with `draw_bitmap`, how the rest of those
+/// tiles combine with earlier drawing depends on the display.
+pub fn fill_rect<D: Display>(disp: &mut D, rect: Rect) -> Result<(), D::Error> {
+    let rect = rect.clip(disp.bounds());
+    for tile_pos in rect.tiles() {
+        let area = Rect::new_sized(tile_pos * TILE_SIZE, Vector(TILE_SIZE, TILE_SIZE));
+        let visible = match area.intersection(rect) {
+            Some(r) => r,
+            None => continue,
+        };
+        let mut bits = 0;
+        for p in visible.points() {
+            bits |= tile_pixel_mask(p - area.start);
+        }
+        disp.draw_tile(bits, tile_pos)?;
+    }
+    Ok(())
+}
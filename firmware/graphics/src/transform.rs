@@ -0,0 +1,282 @@
+use crate::bitmap::tile_pixel_mask;
+use crate::vector::Vector;
+use crate::{Display, Tile, TILE_SIZE};
+
+/// A clockwise rotation by a multiple of 90 degrees.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns the rotation for the given number of degrees, which must be
+    /// a multiple of 90. Negative values rotate anticlockwise.
+    pub fn from_degrees(deg: i32) -> Option<Self> {
+        if deg % 90 != 0 {
+            return None;
+        }
+        Some(match deg.rem_euclid(360) {
+            0 => Rotation::Deg0,
+            90 => Rotation::Deg90,
+            180 => Rotation::Deg180,
+            _ => Rotation::Deg270,
+        })
+    }
+
+    /// Returns true if the rotation exchanges the horizontal and vertical
+    /// axes.
+    pub fn is_sideways(self) -> bool {
+        match self {
+            Rotation::Deg90 | Rotation::Deg270 => true,
+            Rotation::Deg0 | Rotation::Deg180 => false,
+        }
+    }
+}
+
+/// A combination of an optional horizontal mirroring followed by a
+/// rotation.
+///
+/// Vertical mirroring is the same as horizontal mirroring combined with a
+/// rotation of 180 degrees.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub mirror: bool,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        rotation: Rotation::Deg0,
+        mirror: false,
+    };
+
+    pub fn new(rotation: Rotation, mirror: bool) -> Self {
+        Self {
+            rotation: rotation,
+            mirror: mirror,
+        }
+    }
+
+    /// Returns the size of an area of the given size after transformation.
+    pub fn size(self, size: Vector) -> Vector {
+        if self.rotation.is_sideways() {
+            Vector(size.1, size.0)
+        } else {
+            size
+        }
+    }
+
+    /// Maps a point inside an area of the given size, before
+    /// transformation, to the corresponding point in the transformed area.
+    pub fn point(self, p: Vector, size: Vector) -> Vector {
+        let x = if self.mirror { size.0 - 1 - p.0 } else { p.0 };
+        let y = p.1;
+        match self.rotation {
+            Rotation::Deg0 => Vector(x, y),
+            Rotation::Deg90 => Vector(size.1 - 1 - y, x),
+            Rotation::Deg180 => Vector(size.0 - 1 - x, size.1 - 1 - y),
+            Rotation::Deg270 => Vector(y, size.0 - 1 - x),
+        }
+    }
+
+    /// Transforms the pixels within raw tile data, in the format used by
+    /// `Tile::raw_pixel_data`.
+    pub fn tile_bits(self, raw: u16) -> u16 {
+        if self == Self::IDENTITY {
+            return raw;
+        }
+        let size = Vector(TILE_SIZE, TILE_SIZE);
+        let mut ret = 0;
+        for y in 0..TILE_SIZE {
+            for x in 0..TILE_SIZE {
+                if raw & tile_pixel_mask(Vector(x, y)) != 0 {
+                    ret |= tile_pixel_mask(self.point(Vector(x, y), size));
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// Wraps another display so that everything drawn on it is rotated and
+/// optionally mirrored, for panels that are not mounted in their natural
+/// orientation.
+///
+/// The size of this display is the size of the wrapped display after
+/// undoing the transformation, so a 256x64 panel rotated by 90 degrees
+/// presents itself as a 64x256 display.
+pub struct TransformDisplay<D: Display> {
+    inner: D,
+    transform: Transform,
+}
+
+impl<D: Display> TransformDisplay<D> {
+    pub fn new(inner: D, transform: Transform) -> Self {
+        Self {
+            inner: inner,
+            transform: transform,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    pub fn release(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: Display> Display for TransformDisplay<D> {
+    type Error = D::Error;
+    type P = D::P;
+    const OFF: D::P = D::OFF;
+    const ON: D::P = D::ON;
+
+    fn size(&self) -> Vector {
+        // Our transforms are all their own inverse as far as the size is
+        // concerned, so we can use the same method in both directions.
+        self.transform.size(self.inner.size())
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        self.inner.flip()
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.inner.clear()
+    }
+
+    fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        let bounds = self.tile_bounds();
+        if !pos.inside(bounds) {
+            return Ok(());
+        }
+        let phys_pos = self.transform.point(pos, bounds.size());
+        let raw = self.transform.tile_bits(tile.raw_pixel_data());
+        self.inner.draw_tile(raw, phys_pos)
+    }
+
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        // The shift is a direction rather than a position, so we transform
+        // it by how it moves the origin.
+        let size = self.size();
+        let origin = self.transform.point(Vector(0, 0), size);
+        let moved = self.transform.point(shift, size);
+        self.inner.set_shift(moved - origin)
+    }
+
+    fn set_brightness(&mut self, level: u8) -> Result<(), Self::Error> {
+        self.inner.set_brightness(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::FrameBuffer;
+    use crate::vector::Rect;
+
+    // The size of the physical display that the tests draw onto, which is
+    // two tiles wide and three tall so that every rotation is distinct.
+    const PHYS_SIZE: Vector = Vector(8, 12);
+
+    // Lights a single pixel at the given logical position through a
+    // transformed display, and returns where it ended up on the physical
+    // display.
+    fn draw_pixel(transform: Transform, p: Vector) -> Vector {
+        let mut buf = [0u8; 12];
+        let mut disp = TransformDisplay::new(FrameBuffer::new(PHYS_SIZE, &mut buf[..]), transform);
+        let tile = Vector(p.0.div_euclid(TILE_SIZE), p.1.div_euclid(TILE_SIZE));
+        let bits = tile_pixel_mask(p - tile * TILE_SIZE);
+        disp.draw_tile(bits, tile).unwrap();
+
+        let fb = disp.release();
+        let mut found = None;
+        for q in Rect::new(Vector(0, 0), PHYS_SIZE).points() {
+            if fb.get_pixel(q) {
+                assert_eq!(found, None, "more than one pixel lit");
+                found = Some(q);
+            }
+        }
+        found.expect("no pixel lit")
+    }
+
+    #[test]
+    fn rotation_from_degrees() {
+        assert_eq!(Rotation::from_degrees(0), Some(Rotation::Deg0));
+        assert_eq!(Rotation::from_degrees(90), Some(Rotation::Deg90));
+        assert_eq!(Rotation::from_degrees(540), Some(Rotation::Deg180));
+        assert_eq!(Rotation::from_degrees(-90), Some(Rotation::Deg270));
+        assert_eq!(Rotation::from_degrees(45), None);
+    }
+
+    #[test]
+    fn deg0() {
+        let t = Transform::IDENTITY;
+        assert_eq!(t.size(PHYS_SIZE), Vector(8, 12));
+        assert_eq!(draw_pixel(t, Vector(1, 2)), Vector(1, 2));
+        assert_eq!(draw_pixel(t, Vector(6, 9)), Vector(6, 9));
+    }
+
+    #[test]
+    fn deg90() {
+        // The logical display is 12 wide and 8 tall, and its top-left
+        // corner is at the top-right of the physical display.
+        let t = Transform::new(Rotation::Deg90, false);
+        assert_eq!(t.size(PHYS_SIZE), Vector(12, 8));
+        assert_eq!(draw_pixel(t, Vector(0, 0)), Vector(7, 0));
+        assert_eq!(draw_pixel(t, Vector(1, 2)), Vector(5, 1));
+        assert_eq!(draw_pixel(t, Vector(11, 7)), Vector(0, 11));
+    }
+
+    #[test]
+    fn deg180() {
+        let t = Transform::new(Rotation::Deg180, false);
+        assert_eq!(t.size(PHYS_SIZE), Vector(8, 12));
+        assert_eq!(draw_pixel(t, Vector(0, 0)), Vector(7, 11));
+        assert_eq!(draw_pixel(t, Vector(1, 2)), Vector(6, 9));
+    }
+
+    #[test]
+    fn deg270() {
+        // The top-left corner of the logical display is at the bottom-left
+        // of the physical display.
+        let t = Transform::new(Rotation::Deg270, false);
+        assert_eq!(t.size(PHYS_SIZE), Vector(12, 8));
+        assert_eq!(draw_pixel(t, Vector(0, 0)), Vector(0, 11));
+        assert_eq!(draw_pixel(t, Vector(1, 2)), Vector(2, 10));
+        assert_eq!(draw_pixel(t, Vector(11, 7)), Vector(7, 0));
+    }
+
+    #[test]
+    fn mirror() {
+        let t = Transform::new(Rotation::Deg0, true);
+        assert_eq!(t.size(PHYS_SIZE), Vector(8, 12));
+        assert_eq!(draw_pixel(t, Vector(1, 2)), Vector(6, 2));
+
+        // Mirroring happens before the rotation.
+        let t = Transform::new(Rotation::Deg90, true);
+        assert_eq!(draw_pixel(t, Vector(0, 0)), Vector(7, 11));
+        assert_eq!(draw_pixel(t, Vector(1, 2)), Vector(5, 10));
+    }
+
+    #[test]
+    fn off_screen_tiles_are_discarded() {
+        let mut buf = [0u8; 12];
+        let t = Transform::new(Rotation::Deg90, false);
+        let mut disp = TransformDisplay::new(FrameBuffer::new(PHYS_SIZE, &mut buf[..]), t);
+        // In tiles, the logical display is three wide and two tall.
+        for &pos in &[Vector(3, 0), Vector(0, 2), Vector(-1, 0)] {
+            disp.draw_tile(0xffff_u16, pos).unwrap();
+        }
+        assert!(disp.release().release().iter().all(|&b| b == 0));
+    }
+}
@@ -1,13 +1,25 @@
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Vector(pub i32, pub i32);
 
 impl Vector {
-    fn inside(self, rect: Rect) -> bool {
-        let r = rect.normalized();
-        self.0 >= rect.start.0
-            && self.1 >= rect.start.1
-            && self.0 <= rect.end.0
-            && self.1 <= rect.end.1
+    /// Returns true if the vector, interpreted as a point, lies within the
+    /// given rectangle. See `Rect::contains`.
+    pub fn inside(self, rect: Rect) -> bool {
+        rect.contains(self)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Vector(
+            core::cmp::min(self.0, other.0),
+            core::cmp::min(self.1, other.1),
+        )
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Vector(
+            core::cmp::max(self.0, other.0),
+            core::cmp::max(self.1, other.1),
+        )
     }
 }
 
@@ -43,7 +55,12 @@ impl core::ops::Div<i32> for Vector {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+/// A rectangle described by two corners.
+///
+/// The start corner is inclusive and the end corner is exclusive, so
+/// `Rect::new4(0, 0, 8, 1)` covers eight points in a single row and a
+/// rectangle whose start and end are equal on either axis is empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Rect {
     pub start: Vector,
     pub end: Vector,
@@ -64,10 +81,32 @@ impl Rect {
         }
     }
 
+    /// Creates a rectangle of the given size whose start corner is at `pos`.
+    pub fn new_sized(pos: Vector, size: Vector) -> Rect {
+        Rect {
+            start: pos,
+            end: pos + size,
+        }
+    }
+
     pub fn size(self) -> Vector {
         self.end - self.start
     }
 
+    pub fn width(self) -> i32 {
+        self.end.0 - self.start.0
+    }
+
+    pub fn height(self) -> i32 {
+        self.end.1 - self.start.1
+    }
+
+    /// Returns true if the rectangle covers no points at all.
+    pub fn is_empty(self) -> bool {
+        let r = self.normalized();
+        r.start.0 == r.end.0 || r.start.1 == r.end.1
+    }
+
     pub fn normalized(self) -> Self {
         let mut ret = self;
         if ret.start.0 > ret.end.0 {
@@ -83,22 +122,93 @@ impl Rect {
         ret
     }
 
+    /// Returns the part of the receiver that lies within `bounds`.
+    ///
+    /// If the two rectangles do not overlap then the result is an empty
+    /// rectangle positioned on the edge of `bounds` nearest to the receiver.
     pub fn clip(self, bounds: Self) -> Self {
-        let mut ret = self.normalized();
+        let r = self.normalized();
         let lim = bounds.normalized();
-        if ret.start.0 < lim.start.0 {
-            ret.start.0 = lim.start.0;
+        let start = r.start.max(lim.start).min(lim.end);
+        let end = r.end.min(lim.end).max(start);
+        Rect::new(start, end)
+    }
+
+    /// Returns the overlapping area of the two rectangles, or `None` if
+    /// they do not overlap.
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let ret = self.clip(other);
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
         }
-        if ret.start.1 < lim.start.1 {
-            ret.start.1 = lim.start.1;
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    ///
+    /// Empty rectangles contribute nothing to the result, so the union of
+    /// an empty rectangle with another is just the other rectangle.
+    pub fn union(self, other: Self) -> Self {
+        if self.is_empty() {
+            return other.normalized();
         }
-        if ret.end.0 < lim.end.0 {
-            ret.end.0 = lim.end.0;
+        if other.is_empty() {
+            return self.normalized();
         }
-        if ret.end.1 < lim.end.1 {
-            ret.end.1 = lim.end.1;
+        let a = self.normalized();
+        let b = other.normalized();
+        Rect::new(a.start.min(b.start), a.end.max(b.end))
+    }
+
+    /// Returns true if the given point lies within the rectangle.
+    pub fn contains(self, p: Vector) -> bool {
+        let r = self.normalized();
+        p.0 >= r.start.0 && p.1 >= r.start.1 && p.0 < r.end.0 && p.1 < r.end.1
+    }
+
+    /// Returns true if the whole of the given rectangle lies within the
+    /// receiver. An empty rectangle is contained by any rectangle.
+    pub fn contains_rect(self, other: Self) -> bool {
+        if other.is_empty() {
+            return true;
         }
-        ret
+        let r = self.normalized();
+        let o = other.normalized();
+        o.start.0 >= r.start.0 && o.start.1 >= r.start.1 && o.end.0 <= r.end.0 && o.end.1 <= r.end.1
+    }
+
+    /// Returns an iterator over all of the points in the rectangle, in
+    /// row-major order.
+    pub fn points(self) -> Points {
+        Points::new(self.normalized())
+    }
+
+    /// Interprets the receiver as a rectangle in pixel coordinates and
+    /// returns the rectangle of tile positions that covers it, including
+    /// any tiles that it only partially overlaps.
+    pub fn tile_rect(self) -> Self {
+        let r = self.normalized();
+        Rect::new(
+            Vector(
+                floor_div(r.start.0, crate::TILE_SIZE),
+                floor_div(r.start.1, crate::TILE_SIZE),
+            ),
+            Vector(
+                ceil_div(r.end.0, crate::TILE_SIZE),
+                ceil_div(r.end.1, crate::TILE_SIZE),
+            ),
+        )
+    }
+
+    /// Interprets the receiver as a rectangle in pixel coordinates and
+    /// returns an iterator over the positions of all of the tiles that it
+    /// covers, in row-major order.
+    pub fn tiles(self) -> Points {
+        if self.is_empty() {
+            return Points::new(Rect::new(self.start, self.start));
+        }
+        self.tile_rect().points()
     }
 }
 
@@ -112,3 +222,219 @@ impl core::ops::Add<Vector> for Rect {
         }
     }
 }
+
+impl core::ops::Sub<Vector> for Rect {
+    type Output = Self;
+
+    fn sub(self, rhs: Vector) -> Self {
+        Self {
+            start: self.start - rhs,
+            end: self.end - rhs,
+        }
+    }
+}
+
+/// Iterator over the points in a rectangle, returned by `Rect::points` and
+/// `Rect::tiles`.
+#[derive(Clone, Debug)]
+pub struct Points {
+    rect: Rect,
+    next: Vector,
+}
+
+impl Points {
+    fn new(rect: Rect) -> Self {
+        let next = if rect.is_empty() {
+            rect.end
+        } else {
+            rect.start
+        };
+        Self {
+            rect: rect,
+            next: next,
+        }
+    }
+}
+
+impl Iterator for Points {
+    type Item = Vector;
+
+    fn next(&mut self) -> Option<Vector> {
+        if self.next.1 >= self.rect.end.1 {
+            return None;
+        }
+        let ret = self.next;
+        self.next.0 += 1;
+        if self.next.0 >= self.rect.end.0 {
+            self.next.0 = self.rect.start.0;
+            self.next.1 += 1;
+        }
+        Some(ret)
+    }
+}
+
+/// Divides `a` by `b`, rounding towards negative infinity rather than
+/// towards zero, which converts a pixel coordinate to the coordinate of
+/// the tile that contains it even when it's negative.
+pub(crate) fn floor_div(a: i32, b: i32) -> i32 {
+    let q = a / b;
+    if (a % b != 0) && (a < 0) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+fn ceil_div(a: i32, b: i32) -> i32 {
+    -floor_div(-a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::FrameBuffer;
+    use crate::Display;
+
+    #[test]
+    fn clip_overlapping() {
+        let r = Rect::new4(-4, 2, 10, 20);
+        let bounds = Rect::new4(0, 0, 8, 8);
+        assert_eq!(r.clip(bounds), Rect::new4(0, 2, 8, 8));
+        assert_eq!(r.intersection(bounds), Some(Rect::new4(0, 2, 8, 8)));
+        assert_eq!(bounds.intersection(r), Some(Rect::new4(0, 2, 8, 8)));
+    }
+
+    #[test]
+    fn clip_inside_is_unchanged() {
+        let r = Rect::new4(2, 3, 5, 7);
+        assert_eq!(r.clip(Rect::new4(0, 0, 8, 8)), r);
+    }
+
+    #[test]
+    fn clip_disjoint() {
+        let bounds = Rect::new4(0, 0, 8, 8);
+
+        let right = Rect::new4(10, 2, 12, 4).clip(bounds);
+        assert!(right.is_empty());
+        assert_eq!(right, Rect::new4(8, 2, 8, 4));
+
+        let above = Rect::new4(2, -6, 4, -2).clip(bounds);
+        assert!(above.is_empty());
+        assert_eq!(above, Rect::new4(2, 0, 4, 0));
+
+        assert_eq!(Rect::new4(10, 2, 12, 4).intersection(bounds), None);
+        // Rectangles that only share an edge don't overlap.
+        assert_eq!(Rect::new4(8, 0, 12, 8).intersection(bounds), None);
+    }
+
+    #[test]
+    fn clip_reversed_corners() {
+        let r = Rect::new4(6, 6, -2, 2);
+        assert_eq!(r.normalized(), Rect::new4(-2, 2, 6, 6));
+        assert_eq!(r.clip(Rect::new4(0, 0, 4, 4)), Rect::new4(0, 2, 4, 4));
+    }
+
+    #[test]
+    fn empty_rects() {
+        assert!(Rect::new4(3, 3, 3, 8).is_empty());
+        assert!(Rect::new4(3, 3, 8, 3).is_empty());
+        assert!(!Rect::new4(3, 3, 4, 4).is_empty());
+        assert_eq!(
+            Rect::new4(3, 3, 3, 8).intersection(Rect::new4(0, 0, 8, 8)),
+            None
+        );
+        assert_eq!(Rect::new4(3, 3, 3, 8).points().count(), 0);
+        assert_eq!(Rect::new4(3, 3, 3, 8).tiles().count(), 0);
+        assert!(Rect::new4(0, 0, 8, 8).contains_rect(Rect::new4(20, 20, 20, 30)));
+    }
+
+    #[test]
+    fn union() {
+        let a = Rect::new4(0, 0, 4, 4);
+        let b = Rect::new4(-2, 6, 1, 9);
+        assert_eq!(a.union(b), Rect::new4(-2, 0, 4, 9));
+        assert_eq!(b.union(a), Rect::new4(-2, 0, 4, 9));
+
+        let empty = Rect::new4(20, 20, 20, 30);
+        assert_eq!(a.union(empty), a);
+        assert_eq!(empty.union(a), a);
+    }
+
+    #[test]
+    fn contains() {
+        let r = Rect::new4(-3, -3, 2, 2);
+        assert!(r.contains(Vector(-3, -3)));
+        assert!(r.contains(Vector(1, 1)));
+        assert!(!r.contains(Vector(2, 1)));
+        assert!(!r.contains(Vector(1, 2)));
+        assert!(!r.contains(Vector(-4, 0)));
+        assert!(Vector(0, 0).inside(r));
+        assert!(!Vector(0, 0).inside(Rect::new4(0, 0, 0, 0)));
+
+        assert!(r.contains_rect(Rect::new4(-3, -3, 2, 2)));
+        assert!(r.contains_rect(Rect::new4(-1, -1, 1, 1)));
+        assert!(!r.contains_rect(Rect::new4(-1, -1, 3, 1)));
+    }
+
+    #[test]
+    fn points() {
+        let mut it = Rect::new4(-1, 4, 1, 6).points();
+        assert_eq!(it.next(), Some(Vector(-1, 4)));
+        assert_eq!(it.next(), Some(Vector(0, 4)));
+        assert_eq!(it.next(), Some(Vector(-1, 5)));
+        assert_eq!(it.next(), Some(Vector(0, 5)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn tile_rect_rounds_outwards() {
+        // Aligned rectangles convert exactly.
+        assert_eq!(Rect::new4(0, 0, 8, 4).tile_rect(), Rect::new4(0, 0, 2, 1));
+        // Partial tiles are included at both ends.
+        assert_eq!(Rect::new4(1, 3, 9, 5).tile_rect(), Rect::new4(0, 0, 3, 2));
+        // Negative coordinates round away from zero rather than towards it.
+        assert_eq!(
+            Rect::new4(-1, -5, 0, -4).tile_rect(),
+            Rect::new4(-1, -2, 0, -1)
+        );
+        assert_eq!(
+            Rect::new4(-8, -7, -3, -1).tile_rect(),
+            Rect::new4(-2, -2, 0, 0)
+        );
+    }
+
+    #[test]
+    fn tiles() {
+        let mut it = Rect::new4(3, 0, 5, 1).tiles();
+        assert_eq!(it.next(), Some(Vector(0, 0)));
+        assert_eq!(it.next(), Some(Vector(1, 0)));
+        assert_eq!(it.next(), None);
+
+        assert_eq!(Rect::new4(-5, -5, 5, 5).tiles().count(), 4 * 4);
+    }
+
+    #[test]
+    fn draw_tile_clips_at_edges() {
+        let size = Vector(8, 8);
+        let mut buf = [0u8; 8];
+        let mut fb = FrameBuffer::new(size, &mut buf[..]);
+
+        // Tiles entirely off-screen are discarded rather than wrapping
+        // around onto the other side of the display.
+        for &pos in &[Vector(-1, 0), Vector(0, -1), Vector(2, 0), Vector(0, 2)] {
+            fb.draw_tile(0xffff_u16, pos).unwrap();
+        }
+        assert!(fb.release().iter().all(|&b| b == 0));
+
+        let mut buf = [0u8; 8];
+        let mut fb = FrameBuffer::new(size, &mut buf[..]);
+        fb.draw_tile(0xffff_u16, Vector(1, 1)).unwrap();
+        // A tile shifted partly off the bottom-right corner keeps only the
+        // pixels that are still on the display.
+        fb.set_shift(Vector(2, 2)).unwrap();
+        fb.draw_tile(0xffff_u16, Vector(1, 1)).unwrap();
+        for p in Rect::new4(0, 0, 8, 8).points() {
+            assert_eq!(fb.get_pixel(p), p.0 >= 4 && p.1 >= 4, "pixel {:?}", p);
+        }
+    }
+}
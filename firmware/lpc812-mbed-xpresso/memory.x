@@ -1,5 +1,8 @@
 MEMORY
 {
-  FLASH : ORIGIN = 0x00000000, LENGTH = 16K
-  RAM : ORIGIN = 0x10000000, LENGTH = 4K
+  /* The last 64-byte page of flash is reserved for saved settings, and
+     the top 32 bytes of RAM for the ROM's flash programming routines.
+     See src/storage.rs. */
+  FLASH : ORIGIN = 0x00000000, LENGTH = 16K - 64
+  RAM : ORIGIN = 0x10000000, LENGTH = 4K - 32
 }
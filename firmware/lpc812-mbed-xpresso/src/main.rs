@@ -3,12 +3,38 @@
 
 extern crate cortex_m_rt;
 
+use core::sync::atomic::{AtomicBool, Ordering};
+
 use lpc81x_hal as hal;
 use ssd1322::SSD1322;
 
+mod storage;
+
+// The interrupt handlers all run at the same priority, so they can share
+// this queue without locking. The idle loop runs below them, so it must
+// disable interrupts whenever it pushes an event of its own.
+static EVENTS: clockmain::EventQueue = clockmain::EventQueue::new();
+
+// Set by the idle loop while the app is animating, so that SysTick knows to
+// keep delivering frames.
+static ANIMATING: AtomicBool = AtomicBool::new(false);
+
+// The clock chip only measures the temperature every 64 seconds, so we
+// read it about once a minute, which is every 120 ticks.
+const TEMPERATURE_INTERVAL_TICKS: u8 = 120;
+
 #[rtfm::app(device = lpc81x_hal)]
 const APP: () = {
-    static mut EVENTS: clockmain::Events = ();
+    // Set every so often to ask the idle loop to read the clock chip's
+    // thermometer, since only it has access to the clock chip.
+    static mut TEMPERATURE_DUE: bool = false;
+    // The number of ticks until the next temperature reading, which starts
+    // at zero so that the first tick takes one.
+    static mut TEMPERATURE_TICKS: u8 = 0;
+    static mut BUTTONS: Buttons = ();
+    static mut BUTTON_INTS: ButtonInterrupts = ();
+    static mut SYST: cortex_m::peripheral::SYST = ();
+    static mut BUZZER: lpc81x_hal::pins::pin::Pin1<lpc81x_hal::pins::mode::DigitalOutput> = ();
     static mut TICKINT: lpc81x_hal::pinint::int::Interrupt0<
         lpc81x_hal::pinint::mode::Edge<
             lpc81x_hal::pins::pin::Pin6<
@@ -65,6 +91,7 @@ const APP: () = {
                 >,
             >,
         >,
+        storage::FlashStorage,
     > = ();
 
     #[init]
@@ -107,40 +134,232 @@ const APP: () = {
         let pinint0 = pinint.int0.edge_triggered(p.pins.gpio6);
         pinint0.enable(true, true); // Once init complets, PININT0 will be called every half-second
 
-        let app = clockmain::App::new(rtc, disp);
+        // SysTick provides the faster frame tick that drives animations and
+        // samples the buttons. We're running from the 12MHz internal
+        // oscillator, so this gives us 25 frames per second. It stops
+        // itself when there's nothing to animate and no button held, and
+        // pressing a button starts it again.
+        let mut syst = core.SYST;
+        syst.set_clock_source(cortex_m::peripheral::syst::SystClkSource::Core);
+        syst.set_reload(12_000_000 / 25 - 1);
+        syst.clear_current();
+        syst.enable_counter();
+        syst.enable_interrupt();
+
+        let buttons = Buttons {
+            up: p.pin_inputs.gpio17,
+            down: p.pin_inputs.gpio8,
+            select: p.pin_inputs.gpio9,
+            tracker: clockmain::input::ButtonTracker::new(),
+        };
+        let button_ints = ButtonInterrupts {
+            up: pinint.int1.edge_triggered(buttons.up.digital_input()),
+            down: pinint.int2.edge_triggered(buttons.down.digital_input()),
+            select: pinint.int3.edge_triggered(buttons.select.digital_input()),
+        };
+        // The buttons pull their pins low, so a falling edge is a press.
+        button_ints.up.enable(false, true);
+        button_ints.down.enable(false, true);
+        button_ints.select.enable(false, true);
+
+        // The buzzer is driven through a transistor, so it sounds while
+        // its pin is high.
+        let buzzer = p.pins.gpio1.to_digital_output(false);
+
+        let mut app = clockmain::App::with_storage(rtc, disp, storage::FlashStorage);
+        app.set_transition(clockmain::anim::Transition::Morph);
+        program_alarm(&mut app);
 
         init::LateResources {
             APP: app,
-            EVENTS: clockmain::Events::default(),
             TICKINT: pinint0,
+            BUTTONS: buttons,
+            BUTTON_INTS: button_ints,
+            SYST: syst,
+            BUZZER: buzzer,
         }
     }
 
-    #[idle(resources = [EVENTS, APP])]
+    #[idle(resources = [TEMPERATURE_DUE, APP, BUZZER, SYST])]
     fn idle() -> ! {
+        use embedded_hal::digital::v2::OutputPin;
+
         let app = &mut resources.APP;
+        let buzzer = &mut resources.BUZZER;
+        // The number of temperature readings in a row that have failed.
+        let mut temperature_failures = 0;
         loop {
-            // We'll temporarily block all of the exceptions/interrupts that
-            // might update events while we deal with our update step.
-            resources.EVENTS.lock(|events| {
-                if events.has_pending() {
-                    app.update(events);
+            let temperature_due = &mut resources.TEMPERATURE_DUE;
+            if temperature_due.lock(|due| core::mem::replace(due, false)) {
+                let clock = app.clock_mut();
+                let retries = clockmain::fault::CLOCK_RETRIES;
+                match (0..retries).find_map(|_| clock.temperature().ok()) {
+                    Some(temperature) => {
+                        temperature_failures = 0;
+                        let evt = clockmain::Event::Temperature(temperature);
+                        cortex_m::interrupt::free(|_| push_event(evt));
+                    }
+                    None => {
+                        // The last reading stays on the display unless the
+                        // thermometer keeps failing.
+                        temperature_failures += 1;
+                        if temperature_failures >= clockmain::fault::THERMOMETER_FAULT_READINGS {
+                            temperature_failures = 0;
+                            app.report_fault(clockmain::fault::Fault::Thermometer);
+                        }
+                    }
+                }
+            }
+            if !EVENTS.is_empty() {
+                let outputs = app.update(&EVENTS);
+                match outputs.buzzer {
+                    Some(true) => buzzer.set_high().unwrap(),
+                    Some(false) => buzzer.set_low().unwrap(),
+                    None => {}
                 }
-                events.reset();
-            });
-            app.redraw();
+                if outputs.alarm_changed {
+                    program_alarm(app);
+                }
+                let animating = app.is_animating();
+                ANIMATING.store(animating, Ordering::Relaxed);
+                if animating {
+                    resources.SYST.lock(start_frames);
+                }
+            }
+            // A glitch on the display's bus only spoils one frame, so we
+            // just carry on and draw the next one.
+            if app.needs_redraw() {
+                let _ = app.redraw();
+            }
 
             rtfm::export::wfi();
         }
     }
 
-    #[interrupt(resources = [EVENTS, TICKINT])]
+    #[interrupt(resources = [TEMPERATURE_DUE, TEMPERATURE_TICKS, TICKINT])]
     fn PININT0() {
-        resources.EVENTS.tick = true;
+        push_event(clockmain::Event::Tick);
+        let ticks = &mut *resources.TEMPERATURE_TICKS;
+        if *ticks == 0 {
+            *resources.TEMPERATURE_DUE = true;
+            *ticks = TEMPERATURE_INTERVAL_TICKS;
+        }
+        *ticks -= 1;
         resources.TICKINT.acknowledge_events();
     }
+
+    #[interrupt(resources = [BUTTON_INTS, SYST])]
+    fn PININT1() {
+        start_frames(&mut resources.SYST);
+        resources.BUTTON_INTS.up.acknowledge_events();
+    }
+
+    #[interrupt(resources = [BUTTON_INTS, SYST])]
+    fn PININT2() {
+        start_frames(&mut resources.SYST);
+        resources.BUTTON_INTS.down.acknowledge_events();
+    }
+
+    #[interrupt(resources = [BUTTON_INTS, SYST])]
+    fn PININT3() {
+        start_frames(&mut resources.SYST);
+        resources.BUTTON_INTS.select.acknowledge_events();
+    }
+
+    #[exception(resources = [BUTTONS, SYST])]
+    fn SysTick() {
+        let buttons = &mut resources.BUTTONS;
+        if let Some(evt) = buttons.tracker.sample(buttons.pressed()) {
+            push_event(clockmain::Event::Button(evt));
+        }
+        if ANIMATING.load(Ordering::Relaxed) {
+            push_event(clockmain::Event::Frame);
+        } else if buttons.tracker.is_idle() {
+            resources.SYST.disable_counter();
+        }
+    }
 };
 
+// Adds an event to the queue for the idle loop. The queue only fills up if
+// the idle loop has fallen far behind, and then there's nothing better to
+// do with new events than drop them. This must only be called from the
+// interrupt handlers, or with interrupts disabled.
+fn push_event(evt: clockmain::Event) {
+    let _ = EVENTS.push(evt);
+}
+
+// Starts SysTick delivering frames and sampling the buttons, if it isn't
+// already.
+fn start_frames(syst: &mut cortex_m::peripheral::SYST) {
+    if !syst.is_counter_enabled() {
+        syst.clear_current();
+        syst.enable_counter();
+    }
+}
+
+// The buttons each connect their pin to ground when pressed, relying on
+// the pins' internal pull-up resistors, which are enabled at reset.
+struct Buttons {
+    up: hal::pins::pin::Pin17<hal::pins::mode::DigitalInput>,
+    down: hal::pins::pin::Pin8<hal::pins::mode::DigitalInput>,
+    select: hal::pins::pin::Pin9<hal::pins::mode::DigitalInput>,
+    tracker: clockmain::input::ButtonTracker,
+}
+
+// The pin interrupts that restart SysTick when a button is pressed while
+// it's stopped.
+struct ButtonInterrupts {
+    up: hal::pinint::int::Interrupt1<
+        hal::pinint::mode::Edge<hal::pins::pin::Pin17<hal::pins::mode::DigitalInput>>,
+    >,
+    down: hal::pinint::int::Interrupt2<
+        hal::pinint::mode::Edge<hal::pins::pin::Pin8<hal::pins::mode::DigitalInput>>,
+    >,
+    select: hal::pinint::int::Interrupt3<
+        hal::pinint::mode::Edge<hal::pins::pin::Pin9<hal::pins::mode::DigitalInput>>,
+    >,
+}
+
+impl Buttons {
+    fn pressed(&self) -> Option<clockmain::input::Button> {
+        use clockmain::input::Button;
+        use embedded_hal::digital::v2::InputPin;
+
+        if self.up.is_low().unwrap() {
+            Some(Button::Up)
+        } else if self.down.is_low().unwrap() {
+            Some(Button::Down)
+        } else if self.select.is_low().unwrap() {
+            Some(Button::Select)
+        } else {
+            None
+        }
+    }
+}
+
+// Copies the app's next alarm into the RTC's alarm registers. We take our
+// tick from the RTC's square wave output, so for now this doesn't wake us,
+// but it's ready for a low-power mode that sleeps between alarms.
+fn program_alarm<I2C, WRErr, WErr, Display, Storage>(
+    app: &mut clockmain::App<ds3231::DS3231<I2C>, Display, Storage>,
+) where
+    I2C: embedded_hal::blocking::i2c::WriteRead<Error = WRErr>
+        + embedded_hal::blocking::i2c::Write<Error = WErr>,
+    WRErr: core::fmt::Debug,
+    WErr: core::fmt::Debug,
+    Display: graphics::Display,
+    Storage: clockmain::settings::Storage,
+{
+    let alarm = app.next_alarm().map(|next| ds3231::AlarmTime {
+        day: next.day,
+        hour: bcdtime::BCD::from(next.hour),
+        minute: bcdtime::BCD::from(next.minute),
+    });
+    // Nothing wakes us with this alarm yet, so if the clock chip doesn't
+    // respond we just leave it until the next alarm change.
+    let _ = app.clock_mut().set_alarm2(alarm);
+}
+
 fn init_oled<I: ssd1322::interface::Interface>(
     drv: &mut SSD1322<I>,
 ) -> Result<(), ssd1322::Error<I::Error>> {
@@ -0,0 +1,81 @@
+// Settings storage in the last page of the LPC812's on-chip flash, written
+// using the in-application programming (IAP) routines in the boot ROM.
+//
+// memory.x keeps the linker away from this page, and also from the top 32
+// bytes of RAM, which the IAP routines use as scratch space.
+
+// The IAP entry point, as given in the LPC81x user manual. The low bit is
+// set because it's Thumb code.
+const IAP_ENTRY: usize = 0x1fff_1ff1;
+
+const PAGE_SIZE: usize = 64;
+const PAGE_ADDR: usize = 0x3fc0;
+const PAGE: u32 = (PAGE_ADDR / PAGE_SIZE) as u32;
+const SECTOR: u32 = (PAGE_ADDR / 1024) as u32;
+
+// The IAP routines need to know the core clock frequency in kHz. We run
+// from the 12MHz internal oscillator.
+const CCLK_KHZ: u32 = 12_000;
+
+const CMD_PREPARE: u32 = 50;
+const CMD_COPY: u32 = 51;
+const CMD_ERASE_PAGE: u32 = 59;
+
+const STATUS_SUCCESS: u32 = 0;
+
+pub struct FlashStorage;
+
+/// The status code from a failed IAP command.
+#[derive(Debug)]
+pub struct IAPError(pub u32);
+
+// The source of a flash write must be word-aligned.
+#[repr(align(4))]
+struct Page([u8; PAGE_SIZE]);
+
+impl clockmain::settings::Storage for FlashStorage {
+    type Error = IAPError;
+
+    fn load(&mut self, buf: &mut [u8]) -> Result<bool, IAPError> {
+        let page = unsafe { core::slice::from_raw_parts(PAGE_ADDR as *const u8, PAGE_SIZE) };
+        let len = buf.len().min(PAGE_SIZE);
+        buf[..len].copy_from_slice(&page[..len]);
+        // Erased flash reads as all ones, which isn't valid settings, so
+        // the caller will notice if nothing was ever saved.
+        Ok(true)
+    }
+
+    fn save(&mut self, data: &[u8]) -> Result<(), IAPError> {
+        let mut page = Page([0xff; PAGE_SIZE]);
+        let len = data.len().min(PAGE_SIZE);
+        page.0[..len].copy_from_slice(&data[..len]);
+
+        // Flash can't be read while it's being written, and the vector
+        // table is in flash, so no interrupts may be handled meanwhile.
+        cortex_m::interrupt::free(|_| {
+            iap(&[CMD_PREPARE, SECTOR, SECTOR, 0, 0])?;
+            iap(&[CMD_ERASE_PAGE, PAGE, PAGE, CCLK_KHZ, 0])?;
+            iap(&[CMD_PREPARE, SECTOR, SECTOR, 0, 0])?;
+            iap(&[
+                CMD_COPY,
+                PAGE_ADDR as u32,
+                page.0.as_ptr() as u32,
+                PAGE_SIZE as u32,
+                CCLK_KHZ,
+            ])
+        })
+    }
+}
+
+fn iap(command: &[u32; 5]) -> Result<(), IAPError> {
+    let mut result = [0u32; 4];
+    unsafe {
+        let entry: extern "C" fn(*const u32, *mut u32) = core::mem::transmute(IAP_ENTRY);
+        entry(command.as_ptr(), result.as_mut_ptr());
+    }
+    if result[0] == STATUS_SUCCESS {
+        Ok(())
+    } else {
+        Err(IAPError(result[0]))
+    }
+}
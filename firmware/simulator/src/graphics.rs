@@ -1,6 +1,8 @@
 use graphics::vector::Vector;
 use graphics::Tile;
 use sdl2::pixels::Color;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 const OFF: Color = Color {
     r: 0,
@@ -15,13 +17,26 @@ const ON: Color = Color {
     a: 255,
 };
 
+/// The text for the window's title bar, which the main loop can change
+/// even though the app owns the display. The window shows the new title
+/// when the app next draws a frame.
+pub type Title = Rc<RefCell<String>>;
+
 pub struct SDLGraphics {
     canvas: sdl2::render::Canvas<sdl2::video::Window>,
+    shift: Vector,
+    on: Color,
+    title: Title,
 }
 
 impl SDLGraphics {
-    pub fn new(canvas: sdl2::render::Canvas<sdl2::video::Window>) -> Self {
-        Self { canvas: canvas }
+    pub fn new(canvas: sdl2::render::Canvas<sdl2::video::Window>, title: Title) -> Self {
+        Self {
+            canvas: canvas,
+            shift: Vector(0, 0),
+            on: ON,
+            title: title,
+        }
     }
 }
 
@@ -38,6 +53,13 @@ impl graphics::Display for SDLGraphics {
 
     fn flip(&mut self) -> Result<(), Self::Error> {
         self.canvas.present();
+        let title = self.title.borrow();
+        if self.canvas.window().title() != title.as_str() {
+            self.canvas
+                .window_mut()
+                .set_title(&title)
+                .map_err(|err| err.to_string())?;
+        }
         Ok(())
     }
 
@@ -48,9 +70,12 @@ impl graphics::Display for SDLGraphics {
     }
 
     fn draw_tile<TILE: Tile>(&mut self, tile: TILE, pos: Vector) -> Result<(), Self::Error> {
+        if !pos.inside(self.tile_bounds()) {
+            return Ok(());
+        }
         let raw_tile = tile.raw_pixel_data();
-        let raw_pos = pos * 4;
-        self.canvas.set_draw_color(ON);
+        let raw_pos = pos * 4 + self.shift;
+        self.canvas.set_draw_color(self.on);
         for y in 0..4 {
             for x in 0..4 {
                 if tile_get_pixel(raw_tile, Vector(x, y)) {
@@ -61,6 +86,24 @@ impl graphics::Display for SDLGraphics {
         }
         Ok(())
     }
+
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        self.shift = shift;
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, level: u8) -> Result<(), Self::Error> {
+        // Even the dimmest level should still be visible, as on a real
+        // panel.
+        let scale = |v: u8| (v as u32 * (32 + level as u32 * 223 / 255) / 255) as u8;
+        self.on = Color {
+            r: scale(ON.r),
+            g: scale(ON.g),
+            b: scale(ON.b),
+            a: 255,
+        };
+        Ok(())
+    }
 }
 
 fn tile_get_pixel(raw: u16, p: Vector) -> bool {
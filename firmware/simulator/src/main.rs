@@ -8,6 +8,7 @@ use sdl2::keyboard::Keycode;
 
 mod clock;
 mod graphics;
+mod storage;
 
 pub fn main() {
     let sdl_context = sdl2::init().unwrap();
@@ -17,21 +18,39 @@ pub fn main() {
         .build()
         .unwrap();
     let canvas = window.into_canvas().build().unwrap();
-    let disp = graphics::SDLGraphics::new(canvas);
+    let (transform, transition) = parse_args(std::env::args().skip(1));
+    let title = graphics::Title::default();
+    let disp = gfx::transform::TransformDisplay::new(
+        graphics::SDLGraphics::new(canvas, title.clone()),
+        transform,
+    );
 
     let clock = clock::SystemClock();
 
-    let mut app = clockmain::App::new(clock, disp);
-    let mut events = clockmain::Events::default();
+    let storage = storage::FileStorage(storage::FileStorage::default_path());
+
+    let mut app = clockmain::App::with_storage(clock, disp, storage);
+    app.set_transition(transition);
+    let events = clockmain::EventQueue::new();
+    let mut buttons = clockmain::input::ButtonTracker::new();
+    let mut pressed = None;
+    let mut light: u8 = 255;
+    let mut buzzer = false;
+    *title.borrow_mut() = window_title(light, buzzer);
 
     let event_subsystem = sdl_context.event().unwrap();
     event_subsystem.register_custom_event::<TimerEvent>().unwrap();
+    event_subsystem.register_custom_event::<FrameEvent>().unwrap();
 
     let timer_subsystem = sdl_context.timer().unwrap();
     let _timer = timer_subsystem.add_timer(500, Box::from(|| {
         event_subsystem.push_custom_event(TimerEvent(true)).unwrap();
         500
     }));
+    let _frame_timer = timer_subsystem.add_timer(40, Box::from(|| {
+        event_subsystem.push_custom_event(FrameEvent).unwrap();
+        40
+    }));
  
     let mut event_pump = sdl_context.event_pump().unwrap();
     'running: loop {
@@ -41,19 +60,121 @@ pub fn main() {
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
+                Event::KeyDown { keycode: Some(Keycode::F), .. } => {
+                    push(&events, clockmain::Event::NextFace);
+                }
+                // The square bracket keys stand in for an ambient light
+                // sensor, making the room darker or lighter.
+                Event::KeyDown { keycode: Some(Keycode::LeftBracket), .. } => {
+                    light = light.saturating_sub(32);
+                    push(&events, clockmain::Event::Light(light));
+                    *title.borrow_mut() = window_title(light, buzzer);
+                }
+                Event::KeyDown { keycode: Some(Keycode::RightBracket), .. } => {
+                    light = light.saturating_add(32);
+                    push(&events, clockmain::Event::Light(light));
+                    *title.borrow_mut() = window_title(light, buzzer);
+                }
+                Event::KeyDown { keycode: Some(key), .. } => {
+                    if let Some(button) = key_button(key) {
+                        pressed = Some(button);
+                    }
+                }
+                Event::KeyUp { keycode: Some(key), .. } => {
+                    if pressed.is_some() && key_button(key) == pressed {
+                        pressed = None;
+                    }
+                }
                 Event::User { .. } => {
-                    // Our only user event type is our timer event, so we'll
-                    // just assume that's what we've got here.
-                    events.tick = true;
+                    if event.as_user_event_type::<TimerEvent>().is_some() {
+                        push(&events, clockmain::Event::Tick);
+                    }
+                    if event.as_user_event_type::<FrameEvent>().is_some() {
+                        push(&events, clockmain::Event::Frame);
+                        if let Some(evt) = buttons.sample(pressed) {
+                            push(&events, clockmain::Event::Button(evt));
+                        }
+                    }
                 }
                 _ => {}
             }
 
-            app.update(&events);
-            events = clockmain::Events::default();
-            app.redraw();
+            let outputs = app.update(&events);
+            if let Some(on) = outputs.buzzer {
+                // We have no buzzer to sound, so the title bar shows it.
+                buzzer = on;
+                *title.borrow_mut() = window_title(light, buzzer);
+            }
+            if app.needs_redraw() {
+                app.redraw().unwrap();
+            }
         }
     }
 }
 
+// Describes the state of the inputs and outputs that the simulator has no
+// hardware for, to show in the window's title bar.
+fn window_title(light: u8, buzzer: bool) -> String {
+    let mut title = format!("miniclock2 - light {}", light);
+    if buzzer {
+        title.push_str(" - BUZZ");
+    }
+    title
+}
+
 struct TimerEvent(bool);
+
+struct FrameEvent;
+
+// We update the app after every SDL event, so the queue should never fill
+// up; if it somehow does then something is badly wrong.
+fn push(events: &clockmain::EventQueue, evt: clockmain::Event) {
+    events.push(evt).expect("event queue is full");
+}
+
+// The arrow keys and the return key stand in for the clock's buttons.
+fn key_button(key: Keycode) -> Option<clockmain::input::Button> {
+    use clockmain::input::Button;
+
+    match key {
+        Keycode::Up => Some(Button::Up),
+        Keycode::Down => Some(Button::Down),
+        Keycode::Return => Some(Button::Select),
+        _ => None,
+    }
+}
+
+// Interprets the command line arguments "--rotate DEGREES" and "--mirror",
+// which allow simulating a panel that is mounted in an unusual orientation,
+// and "--transition NAME", which selects how the digits of the time change.
+fn parse_args<Args: Iterator<Item = String>>(
+    mut args: Args,
+) -> (gfx::transform::Transform, clockmain::anim::Transition) {
+    use clockmain::anim::Transition;
+    use gfx::transform::{Rotation, Transform};
+
+    let mut ret = Transform::IDENTITY;
+    let mut transition = Transition::Cut;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rotate" => {
+                let deg = args.next().and_then(|v| v.parse().ok());
+                ret.rotation = deg
+                    .and_then(Rotation::from_degrees)
+                    .expect("--rotate requires a multiple of 90 degrees");
+            }
+            "--mirror" => ret.mirror = true,
+            "--transition" => {
+                transition = match args.next().as_ref().map(String::as_str) {
+                    Some("cut") => Transition::Cut,
+                    Some("morph") => Transition::Morph,
+                    Some("roll") => Transition::Roll,
+                    Some("dissolve") => Transition::Dissolve,
+                    _ => panic!("--transition requires one of cut, morph, roll or dissolve"),
+                };
+            }
+            _ => panic!("unsupported argument {:?}", arg),
+        }
+    }
+    (ret, transition)
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+// Keeps the app's settings in a file, so that they survive restarting the
+// simulator just as they'd survive switching off the real clock.
+pub struct FileStorage(pub PathBuf);
+
+impl FileStorage {
+    pub fn default_path() -> PathBuf {
+        std::env::temp_dir().join("miniclock2-settings")
+    }
+}
+
+impl clockmain::settings::Storage for FileStorage {
+    type Error = std::io::Error;
+
+    fn load(&mut self, buf: &mut [u8]) -> Result<bool, std::io::Error> {
+        let data = match std::fs::read(&self.0) {
+            Ok(data) => data,
+            Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok(true)
+    }
+
+    fn save(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
+        std::fs::write(&self.0, data)
+    }
+}
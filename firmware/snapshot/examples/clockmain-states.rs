@@ -0,0 +1,86 @@
+use bcdtime::{DateTime, BCD};
+use graphics::vector::Vector;
+use snapshot::{Format, SnapshotDisplay};
+use std::cell::Cell;
+use std::rc::Rc;
+
+// Renders the clock application at a selection of interesting times and
+// writes each frame as an image into the directory given as the first
+// command line argument (or "snapshots" by default).
+//
+// This doesn't need any display hardware or SDL, so it can run in CI and
+// the resulting images can be compared with snapshot::golden.
+fn main() {
+    let dir = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("snapshots"));
+
+    // (hour, minute, second, weekday, date, month, year)
+    let times = [
+        (0x00, 0x00, 0x00, 0, 0x01, 0x01, 0x20),
+        (0x09, 0x41, 0x30, 2, 0x02, 0x10, 0x26),
+        (0x12, 0x34, 0x56, 4, 0x13, 0x06, 0x26),
+        (0x23, 0x59, 0x59, 6, 0x31, 0x12, 0x26),
+    ];
+
+    let now = Rc::new(Cell::new(times[0]));
+    let clock = FakeClock(now.clone());
+    let disp = SnapshotDisplay::new(Vector(256, 64)).with_output(
+        dir,
+        "clockmain",
+        &[
+            Format::Pbm,
+            #[cfg(feature = "png")]
+            Format::Png,
+        ],
+    );
+    let mut app = clockmain::App::new(clock, disp);
+    let events = clockmain::EventQueue::new();
+
+    for t in times.iter() {
+        now.set(*t);
+        // Each tick toggles the colon, so we'll render two frames for each
+        // time to capture both states.
+        for _ in 0..2 {
+            events.push(clockmain::Event::Tick).unwrap();
+            app.update(&events);
+            app.redraw().unwrap();
+        }
+    }
+
+    // We'll also capture each of the other faces once, at the last time.
+    for _ in 1..clockmain::faces::FaceId::ALL.len() {
+        events.push(clockmain::Event::Temperature(86)).unwrap(); // 21.5 degrees
+        events.push(clockmain::Event::NextFace).unwrap();
+        app.update(&events);
+        app.redraw().unwrap();
+    }
+}
+
+struct FakeClock(Rc<Cell<(u8, u8, u8, u8, u8, u8, u8)>>);
+
+impl bcdtime::Read for FakeClock {
+    type Error = std::convert::Infallible;
+
+    fn read(&mut self) -> Result<DateTime, Self::Error> {
+        let (hour, minute, second, day, date, month, year) = self.0.get();
+        Ok(DateTime {
+            second: BCD::from_raw(second),
+            minute: BCD::from_raw(minute),
+            hour: BCD::from_raw(hour),
+            day: day,
+            date: BCD::from_raw(date),
+            month: BCD::from_raw(month),
+            year: BCD::from_raw(year),
+            hr24: true,
+        })
+    }
+}
+
+impl bcdtime::Write for FakeClock {
+    type Error = std::convert::Infallible;
+
+    fn write(&mut self, _dt: &DateTime) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Helpers for comparing rendered frames against committed "golden" PBM
+//! images.
+//!
+//! When the environment variable `UPDATE_GOLDEN` is set to any non-empty
+//! value, the comparison functions instead overwrite the golden images with
+//! the actual results, so that intentional visual changes can be accepted
+//! by re-running the tests once with that variable set.
+
+use crate::{Format, Image};
+use graphics::vector::Vector;
+use std::path::{Path, PathBuf};
+
+/// The name of the environment variable that switches the comparison
+/// functions into update mode.
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// Describes why a frame did not match its golden image.
+#[derive(Debug)]
+pub enum Mismatch {
+    /// The golden image could not be read.
+    Read(std::io::Error),
+    /// The images have different sizes.
+    Size { expected: Vector, actual: Vector },
+    /// The images differ in some pixels. `first` is the first differing
+    /// pixel in row-major order.
+    Pixels { count: usize, first: Vector },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Mismatch::Read(err) => write!(f, "failed to read golden image: {}", err),
+            Mismatch::Size { expected, actual } => write!(
+                f,
+                "image is {}x{}, but golden image is {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            Mismatch::Pixels { count, first } => write!(
+                f,
+                "{} pixels differ from the golden image, starting at ({}, {})",
+                count, first.0, first.1
+            ),
+        }
+    }
+}
+
+/// Compares two images pixel by pixel.
+pub fn diff(expected: &Image, actual: &Image) -> Result<(), Mismatch> {
+    if expected.size() != actual.size() {
+        return Err(Mismatch::Size {
+            expected: expected.size(),
+            actual: actual.size(),
+        });
+    }
+
+    let want = expected.bitmap();
+    let got = actual.bitmap();
+    let mut count = 0;
+    let mut first = None;
+    for p in want.bounds().points() {
+        if want.get_pixel(p) != got.get_pixel(p) {
+            count += 1;
+            first = first.or(Some(p));
+        }
+    }
+    match first {
+        Some(first) => Err(Mismatch::Pixels { count, first }),
+        None => Ok(()),
+    }
+}
+
+/// Compares an image with the golden PBM image at the given path.
+///
+/// If they differ, the actual image is written alongside the golden image
+/// with ".actual.pbm" appended to its name, to help with investigating the
+/// problem. In update mode the golden image is overwritten instead and the
+/// result is always successful.
+pub fn compare<P: AsRef<Path>>(actual: &Image, path: P) -> Result<(), Mismatch> {
+    let path = path.as_ref();
+    if update_mode() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(Mismatch::Read)?;
+        }
+        actual.write(path, Format::Pbm).map_err(Mismatch::Read)?;
+        return Ok(());
+    }
+
+    let expected = Image::read_pbm(path).map_err(Mismatch::Read)?;
+    let result = diff(&expected, actual);
+    if result.is_err() {
+        // This is just a debugging aid, so we'll ignore errors here and
+        // report the mismatch itself.
+        let _ = actual.write(actual_path(path), Format::Pbm);
+    }
+    result
+}
+
+/// Like `compare`, but panics with a descriptive message if the images do
+/// not match. This is intended for use in tests.
+pub fn assert_matches<P: AsRef<Path>>(actual: &Image, path: P) {
+    let path = path.as_ref();
+    if let Err(err) = compare(actual, path) {
+        panic!(
+            "{}: {}\n(set {}=1 to accept the new result)\nactual image:\n{}",
+            path.display(),
+            err,
+            UPDATE_ENV,
+            actual.to_ascii(),
+        );
+    }
+}
+
+fn update_mode() -> bool {
+    std::env::var_os(UPDATE_ENV).is_some_and(|v| !v.is_empty())
+}
+
+fn actual_path(golden: &Path) -> PathBuf {
+    let mut name = golden.as_os_str().to_owned();
+    name.push(".actual.pbm");
+    PathBuf::from(name)
+}
@@ -0,0 +1,261 @@
+//! A headless implementation of `graphics::Display` that renders into
+//! memory, for visual regression testing of drawing code without any real
+//! display hardware or SDL.
+//!
+//! Each frame presented with `flip` is retained and can optionally be
+//! written to disk as an image file. The `golden` functions compare a frame
+//! against a previously-saved reference image.
+
+use graphics::bitmap::Bitmap;
+use graphics::framebuffer::FrameBuffer;
+use graphics::vector::Vector;
+use graphics::{Display, Tile};
+use std::path::{Path, PathBuf};
+
+pub mod golden;
+
+/// The file formats that `SnapshotDisplay` can write frames in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Binary ("P4") portable bitmap.
+    Pbm,
+    /// Grayscale PNG. Available only when the "png" feature is enabled.
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Pbm => "pbm",
+            #[cfg(feature = "png")]
+            Format::Png => "png",
+        }
+    }
+}
+
+/// A monochrome image that owns its pixel data, in the layout used by
+/// `Bitmap`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    size: Vector,
+    data: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an empty image of the given size.
+    pub fn new(size: Vector) -> Self {
+        Self {
+            size: size,
+            data: vec![0; FrameBuffer::<Vec<u8>>::buffer_size(size)],
+        }
+    }
+
+    /// Creates an image by copying the pixels of a bitmap.
+    ///
+    /// Panics if the bitmap has less pixel data than its size calls for.
+    pub fn from_bitmap(bmp: &Bitmap) -> Self {
+        let mut ret = Self::new(bmp.size());
+        let len = ret.data.len();
+        assert!(
+            bmp.data().len() >= len,
+            "a {}x{} bitmap needs {} bytes of pixel data, but has only {}",
+            bmp.width(),
+            bmp.height(),
+            len,
+            bmp.data().len(),
+        );
+        ret.data.copy_from_slice(&bmp.data()[..len]);
+        ret
+    }
+
+    /// Reads a binary ("P4") PBM file.
+    pub fn read_pbm<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let raw = std::fs::read(path.as_ref())?;
+        match Bitmap::from_pbm(&raw) {
+            Some(bmp) => Ok(Self::from_bitmap(&bmp)),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} is not a binary PBM file", path.as_ref().display()),
+            )),
+        }
+    }
+
+    pub fn size(&self) -> Vector {
+        self.size
+    }
+
+    pub fn bitmap(&self) -> Bitmap<'_> {
+        Bitmap::new(self.size.0, self.size.1, &self.data[..])
+    }
+
+    /// Encodes the image as a binary ("P4") PBM file.
+    pub fn encode_pbm(&self) -> Vec<u8> {
+        let mut ret = format!("P4\n{} {}\n", self.size.0, self.size.1).into_bytes();
+        ret.extend_from_slice(&self.data[..]);
+        ret
+    }
+
+    /// Encodes the image as a grayscale PNG file.
+    #[cfg(feature = "png")]
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut ret = Vec::new();
+        {
+            let mut enc = png::Encoder::new(&mut ret, self.size.0 as u32, self.size.1 as u32);
+            enc.set_color(png::ColorType::Grayscale);
+            enc.set_depth(png::BitDepth::One);
+            let mut w = enc.write_header().unwrap();
+            // PBM uses 1 for black while PNG uses 1 for white, but we want
+            // set pixels to look lit in both, so we must invert here.
+            let inverted: Vec<u8> = self.data.iter().map(|b| !b).collect();
+            w.write_image_data(&inverted[..]).unwrap();
+        }
+        ret
+    }
+
+    /// Writes the image to the given path in the given format.
+    pub fn write<P: AsRef<Path>>(&self, path: P, format: Format) -> std::io::Result<()> {
+        let raw = match format {
+            Format::Pbm => self.encode_pbm(),
+            #[cfg(feature = "png")]
+            Format::Png => self.encode_png(),
+        };
+        std::fs::write(path, raw)
+    }
+
+    /// Renders the image as text with one character per pixel, which is
+    /// useful in test failure messages.
+    pub fn to_ascii(&self) -> String {
+        let bmp = self.bitmap();
+        let mut ret = String::with_capacity(((self.size.0 + 1) * self.size.1) as usize);
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                ret.push(if bmp.get_pixel(Vector(x, y)) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+}
+
+/// A `Display` that renders into memory.
+///
+/// Drawing happens on a back buffer, and `flip` copies it into the list of
+/// frames that can be retrieved with `frames` or `last_frame`. If an output
+/// directory is configured, `flip` also writes each frame to a file there.
+pub struct SnapshotDisplay {
+    back: FrameBuffer<Vec<u8>>,
+    frames: Vec<Image>,
+    next_seq: usize,
+    output: Option<Output>,
+}
+
+struct Output {
+    dir: PathBuf,
+    name: String,
+    formats: Vec<Format>,
+}
+
+impl SnapshotDisplay {
+    /// Creates a display of the given size in pixels.
+    pub fn new(size: Vector) -> Self {
+        Self {
+            back: FrameBuffer::new(size, vec![0; FrameBuffer::<Vec<u8>>::buffer_size(size)]),
+            frames: Vec::new(),
+            next_seq: 0,
+            output: None,
+        }
+    }
+
+    /// Arranges for each flipped frame to be written into the given
+    /// directory in each of the given formats, with filenames made from the
+    /// given name and a sequence number, such as "name-0000.pbm".
+    pub fn with_output<P: Into<PathBuf>, S: Into<String>>(
+        mut self,
+        dir: P,
+        name: S,
+        formats: &[Format],
+    ) -> Self {
+        self.output = Some(Output {
+            dir: dir.into(),
+            name: name.into(),
+            formats: formats.to_vec(),
+        });
+        self
+    }
+
+    /// Returns all of the frames flipped so far, oldest first.
+    pub fn frames(&self) -> &[Image] {
+        &self.frames[..]
+    }
+
+    pub fn last_frame(&self) -> Option<&Image> {
+        self.frames.last()
+    }
+
+    /// Returns the contents of the back buffer, which will become visible
+    /// on the next call to `flip`.
+    pub fn back_buffer(&self) -> Bitmap<'_> {
+        self.back.as_bitmap()
+    }
+
+    /// Discards all of the frames retained so far. Frame numbering in
+    /// output filenames continues regardless.
+    pub fn clear_frames(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Returns the path that frame number `seq` would be written to in the
+    /// given format, if an output directory is configured.
+    pub fn frame_path(&self, seq: usize, format: Format) -> Option<PathBuf> {
+        self.output.as_ref().map(|out| {
+            out.dir
+                .join(format!("{}-{:04}.{}", out.name, seq, format.extension()))
+        })
+    }
+}
+
+impl Display for SnapshotDisplay {
+    type Error = std::io::Error;
+    type P = bool;
+    const ON: bool = true;
+    const OFF: bool = false;
+
+    fn size(&self) -> Vector {
+        self.back.size()
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        let frame = Image::from_bitmap(&self.back.as_bitmap());
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if let Some(out) = &self.output {
+            std::fs::create_dir_all(&out.dir)?;
+            for format in out.formats.iter() {
+                frame.write(self.frame_path(seq, *format).unwrap(), *format)?;
+            }
+        }
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        // Our framebuffer cannot fail.
+        self.back.clear().unwrap();
+        Ok(())
+    }
+
+    fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        self.back.draw_tile(tile, pos).unwrap();
+        Ok(())
+    }
+
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        self.back.set_shift(shift).unwrap();
+        Ok(())
+    }
+}
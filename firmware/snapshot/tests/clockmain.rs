@@ -0,0 +1,247 @@
+// Renders each of the clock's faces and screens and compares them with the
+// golden images in tests/golden. Run with UPDATE_GOLDEN=1 to accept
+// intentional changes.
+
+use bcdtime::{DateTime, BCD};
+use clockmain::alarm::Alarm;
+use clockmain::faces::{FaceId, SecondsStyle};
+use clockmain::input::{Button, ButtonEvent, Press};
+use clockmain::locale::Language;
+use clockmain::settings::{self, Settings};
+use clockmain::{App, Event, EventQueue};
+use graphics::vector::Vector;
+use graphics::Tile;
+use snapshot::{golden, Image, SnapshotDisplay};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Wednesday 14 October 2026, at 09:41:30.
+const TIME: (u8, u8, u8, u8, u8, u8, u8) = (0x09, 0x41, 0x30, 2, 0x14, 0x10, 0x26);
+
+fn datetime(t: (u8, u8, u8, u8, u8, u8, u8)) -> DateTime {
+    let (hour, minute, second, day, date, month, year) = t;
+    DateTime {
+        second: BCD::from_raw(second),
+        minute: BCD::from_raw(minute),
+        hour: BCD::from_raw(hour),
+        day: day,
+        date: BCD::from_raw(date),
+        month: BCD::from_raw(month),
+        year: BCD::from_raw(year),
+        hr24: true,
+    }
+}
+
+// Draws the app as it appears after a single tick with the given settings
+// and time, with the given events delivered along with the tick, and
+// compares the result with the named golden image.
+fn check(name: &str, settings: Settings, now: Option<DateTime>, events: &[Event]) {
+    let disp = Shared(Rc::new(RefCell::new(SnapshotDisplay::new(Vector(256, 64)))));
+    let mut app = App::with_storage(FakeClock(now), disp.clone(), FakeStorage(settings));
+    let queue = EventQueue::new();
+    queue.push(Event::Tick).unwrap();
+    for evt in events.iter() {
+        queue.push(*evt).unwrap();
+    }
+    app.update(&queue);
+    app.redraw().unwrap();
+
+    let frame: Image = disp.0.borrow().last_frame().unwrap().clone();
+    let path = format!("{}/tests/golden/{}.pbm", env!("CARGO_MANIFEST_DIR"), name);
+    golden::assert_matches(&frame, path);
+}
+
+fn check_face(name: &str, face: FaceId, events: &[Event]) {
+    let settings = Settings {
+        face: face,
+        ..Settings::default()
+    };
+    check(name, settings, Some(datetime(TIME)), events);
+}
+
+#[test]
+fn classic() {
+    check_face("classic", FaceId::Classic, &[]);
+}
+
+#[test]
+fn bigtime() {
+    check_face("bigtime", FaceId::BigTime, &[]);
+}
+
+#[test]
+fn seconds() {
+    check_face("seconds", FaceId::Seconds, &[]);
+}
+
+#[test]
+fn fulldate() {
+    check_face("fulldate", FaceId::FullDate, &[]);
+}
+
+#[test]
+fn temperature() {
+    // 21.5 degrees.
+    let temperature = Event::Temperature(86);
+    check_face("temperature", FaceId::Temperature, &[temperature]);
+}
+
+#[test]
+fn countdown() {
+    check_face("countdown", FaceId::Countdown, &[]);
+}
+
+#[test]
+fn stopwatch() {
+    check_face("stopwatch", FaceId::Stopwatch, &[]);
+}
+
+#[test]
+fn worldclock() {
+    check_face("worldclock", FaceId::WorldClock, &[]);
+}
+
+#[test]
+fn suntimes() {
+    check_face("suntimes", FaceId::SunTimes, &[]);
+}
+
+#[test]
+fn classic_12_hour() {
+    let settings = Settings {
+        hr24: false,
+        ..Settings::default()
+    };
+    let time = (0x21, 0x05, 0x00, 6, 0x01, 0x11, 0x26);
+    check("classic-12-hour", settings, Some(datetime(time)), &[]);
+}
+
+#[test]
+fn seconds_styles() {
+    let now = Some(datetime(TIME));
+    let mut settings = Settings {
+        seconds: SecondsStyle::Digits,
+        ..Settings::default()
+    };
+    check("classic-seconds-digits", settings, now, &[]);
+    settings.seconds = SecondsStyle::Bar;
+    check("classic-seconds-bar", settings, now, &[]);
+    settings.face = FaceId::BigTime;
+    check("bigtime-seconds-bar", settings, now, &[]);
+}
+
+#[test]
+fn languages() {
+    let mut settings = Settings {
+        face: FaceId::FullDate,
+        language: Language::German,
+        ..Settings::default()
+    };
+    check("fulldate-german", settings, Some(datetime(TIME)), &[]);
+    // The French ordinal suffix is only used on the first of the month.
+    settings.language = Language::French;
+    let time = (0x09, 0x41, 0x30, 6, 0x01, 0x03, 0x26);
+    check("fulldate-french", settings, Some(datetime(time)), &[]);
+    settings.language = Language::Spanish;
+    check("fulldate-spanish", settings, Some(datetime(TIME)), &[]);
+}
+
+#[test]
+fn menu() {
+    let press = ButtonEvent::new(Button::Select, Press::Long);
+    check_face("menu", FaceId::Classic, &[Event::Button(press)]);
+}
+
+#[test]
+fn alarm_ringing() {
+    let mut settings = Settings::default();
+    settings.alarms[0] = Alarm {
+        enabled: true,
+        hour: 9,
+        minute: 41,
+        days: Alarm::EVERY_DAY,
+    };
+    check("alarm-ringing", settings, Some(datetime(TIME)), &[]);
+}
+
+#[test]
+fn clock_fault() {
+    check("clock-fault", Settings::default(), None, &[]);
+}
+
+#[test]
+fn screen_saver() {
+    let settings = Settings {
+        saver: true,
+        ..Settings::default()
+    };
+    let time = (0x02, 0x17, 0x00, 2, 0x14, 0x10, 0x26);
+    check("screen-saver", settings, Some(datetime(time)), &[]);
+}
+
+// A clock that always reads the given time, or always fails if there's no
+// time.
+struct FakeClock(Option<DateTime>);
+
+impl bcdtime::Read for FakeClock {
+    type Error = ();
+
+    fn read(&mut self) -> Result<DateTime, ()> {
+        self.0.ok_or(())
+    }
+}
+
+impl bcdtime::Write for FakeClock {
+    type Error = ();
+
+    fn write(&mut self, _dt: &DateTime) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+// Storage that starts out holding the given settings.
+struct FakeStorage(Settings);
+
+impl settings::Storage for FakeStorage {
+    type Error = std::convert::Infallible;
+
+    fn load(&mut self, buf: &mut [u8]) -> Result<bool, Self::Error> {
+        buf[..settings::SIZE].copy_from_slice(&self.0.encode()[..]);
+        Ok(true)
+    }
+
+    fn save(&mut self, _data: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+// Lets the test look at the frames of a display that the app owns.
+#[derive(Clone)]
+struct Shared(Rc<RefCell<SnapshotDisplay>>);
+
+impl graphics::Display for Shared {
+    type Error = std::io::Error;
+    type P = bool;
+    const ON: bool = true;
+    const OFF: bool = false;
+
+    fn size(&self) -> Vector {
+        self.0.borrow().size()
+    }
+
+    fn flip(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().flip()
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().clear()
+    }
+
+    fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        self.0.borrow_mut().draw_tile(tile, pos)
+    }
+
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        self.0.borrow_mut().set_shift(shift)
+    }
+}
@@ -1,11 +1,17 @@
 use graphics::vector::Vector;
 use graphics::Tile;
 
+// The range of contrast currents that `set_brightness` uses. The maximum is
+// the one recommended for the NHD-3.12-25664UCY2 module.
+const MIN_CONTRAST_CURRENT: u8 = 0x10;
+const MAX_CONTRAST_CURRENT: u8 = 0x9f;
+
 pub struct Display<I: crate::interface::Interface> {
     drv: crate::SSD1322<I>,
     size: graphics::vector::Vector,
     col_offset: u8,
     next_page: i32,
+    shift: Vector,
 }
 
 impl<I, CommsErr> Display<I>
@@ -19,6 +25,7 @@ where
             size: size,
             col_offset: col_offset,
             next_page: 1,
+            shift: Vector(0, 0),
         }
     }
 }
@@ -60,20 +67,67 @@ where
     }
 
     fn draw_tile<T: Tile>(&mut self, tile: T, pos: Vector) -> Result<(), Self::Error> {
+        // The column and row addresses are only eight bits wide, so anything
+        // outside of the visible area would otherwise wrap around and
+        // corrupt some other part of the display memory.
+        if !pos.inside(self.tile_bounds()) {
+            return Ok(());
+        }
+
         let to_draw = DrawTile::new(tile);
 
-        let phys_pos = Vector(pos.0, pos.1 * 4);
+        // Each column address covers four pixels, so we can only shift
+        // horizontally in steps of a whole tile. Rounding the shift away
+        // from zero means a shift of a single pixel still moves the image,
+        // by a whole tile. Rows are addressed individually.
+        let cols = (self.shift.0.abs() + 3) / 4 * self.shift.0.signum();
+        let phys_pos = Vector(pos.0 + cols, pos.1 * 4 + self.shift.1);
+        if phys_pos.0 < 0 || phys_pos.0 >= self.size.0 / 4 {
+            return Ok(());
+        }
+        // A shifted tile may hang off the top or bottom of the display, in
+        // which case we write only the rows that are visible.
+        let first = (-phys_pos.1).max(0);
+        let last = (self.size.1 - phys_pos.1).min(4);
+        if first >= last {
+            return Ok(());
+        }
+
         let offset = Vector(self.col_offset as i32, self.next_page * self.size.1);
         let real_pos = phys_pos + offset;
         let col_addr = real_pos.0 as u8;
-        let row_addr = real_pos.1 as u8;
+        let first_row = (real_pos.1 + first) as u8;
+        let last_row = (real_pos.1 + last - 1) as u8;
         let raw_bytes = to_draw.raw_gdram_data();
         self.drv.set_column_addresses(col_addr, col_addr)?;
-        self.drv.set_row_addresses(row_addr, row_addr + 3)?;
-        self.drv.write_gdram(&raw_bytes[..])?;
+        self.drv.set_row_addresses(first_row, last_row)?;
+        // Each row of the tile takes two bytes.
+        self.drv
+            .write_gdram(&raw_bytes[first as usize * 2..last as usize * 2])?;
+
+        Ok(())
+    }
 
+    fn set_shift(&mut self, shift: Vector) -> Result<(), Self::Error> {
+        self.shift = shift;
         Ok(())
     }
+
+    fn set_brightness(&mut self, level: u8) -> Result<(), Self::Error> {
+        // The master contrast control scales all of the segment currents in
+        // sixteen coarse steps, so we use it only for the dimmest levels and
+        // otherwise adjust the contrast current itself, up to the level
+        // recommended for our panel.
+        let (master, contrast) = if level < 16 {
+            (level, MIN_CONTRAST_CURRENT)
+        } else {
+            let range = (MAX_CONTRAST_CURRENT - MIN_CONTRAST_CURRENT) as u16;
+            let extra = (level - 16) as u16 * range / (255 - 16);
+            (0x0f, MIN_CONTRAST_CURRENT + extra as u8)
+        };
+        self.drv.set_master_contrast_current_control(master)?;
+        self.drv.set_contrast_current(contrast)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -119,3 +173,70 @@ impl Copy for DrawTile {}
 fn raw_buffer_size(size: graphics::vector::Vector) -> usize {
     (size.0 as usize / 2) * size.1 as usize // Each byte contains data for two pixels
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphics::Display as _;
+
+    // An interface that remembers the column addresses most recently set,
+    // and accepts everything else without doing anything.
+    struct Columns(Option<(u8, u8)>);
+
+    impl crate::interface::Interface for Columns {
+        type Error = ();
+
+        fn cmd_0(&mut self, _cmd: u8) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn cmd_1(&mut self, _cmd: u8, _a: u8) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn cmd_2(&mut self, cmd: u8, a: u8, b: u8) -> Result<(), ()> {
+            if cmd == 0x15 {
+                self.0 = Some((a, b));
+            }
+            Ok(())
+        }
+
+        fn cmd_n(&mut self, _cmd: u8, _data: &[u8]) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn cmd_n_iter<I: core::iter::IntoIterator<Item = u8>>(
+            &mut self,
+            _cmd: u8,
+            data: I,
+        ) -> Result<usize, ()> {
+            Ok(data.into_iter().count())
+        }
+    }
+
+    // Draws a tile at the given position with the given shift, returning
+    // the column address it was written to.
+    fn column(shift: Vector, pos: Vector) -> Option<u8> {
+        let drv = crate::SSD1322::new(Columns(None));
+        let mut disp = Display::new(drv, Vector(256, 64), 28);
+        disp.set_shift(shift).unwrap();
+        disp.draw_tile(0xffffu16, pos).unwrap();
+        (disp.drv.0).0.map(|(start, end)| {
+            assert_eq!(start, end);
+            start
+        })
+    }
+
+    #[test]
+    fn horizontal_shift() {
+        assert_eq!(column(Vector(0, 0), Vector(10, 0)), Some(38));
+        // A shift of a single pixel moves by a whole column either way.
+        assert_eq!(column(Vector(1, 0), Vector(10, 0)), Some(39));
+        assert_eq!(column(Vector(-1, 1), Vector(10, 0)), Some(37));
+        assert_eq!(column(Vector(4, 0), Vector(10, 0)), Some(39));
+        assert_eq!(column(Vector(5, 0), Vector(10, 0)), Some(40));
+        // Tiles shifted off either edge aren't drawn at all.
+        assert_eq!(column(Vector(-1, 0), Vector(0, 0)), None);
+        assert_eq!(column(Vector(1, 0), Vector(63, 0)), None);
+    }
+}
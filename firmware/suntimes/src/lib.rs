@@ -0,0 +1,271 @@
+#![no_std]
+
+//! Calculates the times of sunrise and sunset at a given place.
+//!
+//! This uses the approximations from NOAA's "General Solar Position
+//! Calculations", which are good to within a minute or two away from the
+//! polar regions. Everything is done in fixed-point integer arithmetic,
+//! because `core` has no trigonometric functions and the microcontrollers
+//! we run on have no floating point unit.
+
+use bcdtime::DateTime;
+
+/// A place on the Earth's surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// Tenths of a degree north of the equator, or negative for south.
+    pub latitude: i16,
+    /// Tenths of a degree east of Greenwich, or negative for west.
+    pub longitude: i16,
+}
+
+impl Location {
+    pub const fn new(latitude: i16, longitude: i16) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+}
+
+/// The result of `sun_times`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SunTimes {
+    /// The sun rises and sets at the given times, in minutes after UTC
+    /// midnight at the start of the date. These may be negative or more
+    /// than a day for places far from Greenwich, whose local day doesn't
+    /// line up with the UTC one.
+    Rises { sunrise: i16, sunset: i16 },
+    /// The sun stays above the horizon all day, as in a polar summer.
+    AlwaysUp,
+    /// The sun stays below the horizon all day, as in a polar winter.
+    AlwaysDown,
+}
+
+/// Calculates when the sun rises and sets on the date of the given date and
+/// time at the given place. The time of day is ignored.
+pub fn sun_times(dt: &DateTime, loc: &Location) -> SunTimes {
+    let year: u8 = dt.year.into();
+    let year = 2000 + year as u16;
+    let month: u8 = dt.month.into();
+    let date: u8 = dt.date.into();
+    let mut day_of_year = date as i32 - 1;
+    for m in 1..month {
+        day_of_year += bcdtime::days_in_month(year, m) as i32;
+    }
+
+    // The angle of the Earth around its orbit, measured from the start of
+    // the year, at around noon.
+    let days = if bcdtime::is_leap_year(year) { 366 } else { 365 };
+    let orbit = day_of_year * TURN / days;
+    let terms = [
+        sin(orbit),
+        cos(orbit),
+        sin(2 * orbit),
+        cos(2 * orbit),
+        sin(3 * orbit),
+        cos(3 * orbit),
+    ];
+    let sum = |coeffs: &[i32; 6], constant: i32| {
+        let dot: i32 = coeffs.iter().zip(terms.iter()).map(|(c, t)| c * t).sum();
+        constant + dot / ONE
+    };
+    // The sun's declination, as an angle.
+    let decl = sum(&DECLINATION, DECLINATION_CONSTANT);
+    // How far sundials run ahead of clocks, in seconds.
+    let eqtime = sum(&EQUATION_OF_TIME, EQUATION_OF_TIME_CONSTANT);
+
+    // Find how far the Earth turns between sunrise and noon, which is when
+    // the top of the sun is just above the horizon allowing for
+    // refraction.
+    let lat = loc.latitude as i32 * TURN / 3600;
+    let num = SIN_SUNRISE_ELEVATION * ONE - sin(lat) * sin(decl);
+    let den = cos(lat) * cos(decl) / ONE;
+    if den <= 0 {
+        // At the poles the sun circles at the same elevation all day.
+        return if sin(lat) * sin(decl) > 0 {
+            SunTimes::AlwaysUp
+        } else {
+            SunTimes::AlwaysDown
+        };
+    }
+    let cos_angle = num / den;
+    if cos_angle >= ONE {
+        return SunTimes::AlwaysDown;
+    }
+    if cos_angle <= -ONE {
+        return SunTimes::AlwaysUp;
+    }
+    // Both of these are divisible by 128, which keeps the product in range.
+    let half_day = acos(cos_angle) * (SECONDS_PER_DAY / 128) / (TURN / 128);
+
+    // Solar noon is earlier further east, by four minutes per degree.
+    let noon = SECONDS_PER_DAY / 2 - loc.longitude as i32 * 24 - eqtime;
+    SunTimes::Rises {
+        sunrise: round_minutes(noon - half_day),
+        sunset: round_minutes(noon + half_day),
+    }
+}
+
+// Angles are measured in 65536ths of a full turn, and the results of sin
+// and cos are fixed-point numbers with fifteen fractional bits.
+const TURN: i32 = 1 << 16;
+const ONE: i32 = 1 << 15;
+
+const SECONDS_PER_DAY: i32 = 24 * 60 * 60;
+
+// The sine of the elevation of the centre of the sun at sunrise, which is
+// -0.833 degrees.
+const SIN_SUNRISE_ELEVATION: i32 = -476;
+
+// The coefficients of NOAA's series for the declination, converted to our
+// angle units, for each of the terms in the order used in `sun_times`.
+const DECLINATION_CONSTANT: i32 = 72;
+const DECLINATION: [i32; 6] = [733, -4171, 9, -70, 15, -28];
+
+// Likewise for the equation of time, in seconds.
+const EQUATION_OF_TIME_CONSTANT: i32 = 1;
+const EQUATION_OF_TIME: [i32; 6] = [-441, 26, -562, -201, 0, 0];
+
+fn sin(angle: i32) -> i32 {
+    let angle = angle & (TURN - 1);
+    let quarter = TURN / 4;
+    // Reflect into the first quarter turn, where we can use a polynomial.
+    let (x, negate) = match angle / quarter {
+        0 => (angle, false),
+        1 => (2 * quarter - angle, false),
+        2 => (angle - 2 * quarter, true),
+        _ => (TURN - angle, true),
+    };
+    // Scale to a fraction of a quarter turn, and then approximate
+    // sin(x * pi / 2) with an odd polynomial, which is accurate to within
+    // about 0.0002.
+    let x = x * ONE / quarter;
+    let x2 = x * x / ONE;
+    let mut ret = 2611 * x2 / ONE - 21167;
+    ret = ret * x2 / ONE + 51472;
+    ret = ret * x / ONE;
+    if negate {
+        -ret
+    } else {
+        ret
+    }
+}
+
+fn cos(angle: i32) -> i32 {
+    sin(angle + TURN / 4)
+}
+
+// Returns the angle between zero and half a turn whose cosine is the given
+// value, by bisection.
+fn acos(v: i32) -> i32 {
+    let (mut lo, mut hi) = (0, TURN / 2);
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if cos(mid) > v {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+fn round_minutes(seconds: i32) -> i16 {
+    let minutes = if seconds < 0 {
+        (seconds - 30) / 60
+    } else {
+        (seconds + 30) / 60
+    };
+    minutes as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bcdtime::BCD;
+
+    fn on(year: u8, month: u8, date: u8) -> DateTime {
+        DateTime {
+            second: BCD::from(0),
+            minute: BCD::from(0),
+            hour: BCD::from(12),
+            day: 0,
+            date: BCD::from(date),
+            month: BCD::from(month),
+            year: BCD::from(year),
+            hr24: true,
+        }
+    }
+
+    // Checks that the sun rises and sets within two minutes of the given
+    // times, which come from NOAA's own calculation.
+    fn assert_rises(got: SunTimes, sunrise: i16, sunset: i16) {
+        match got {
+            SunTimes::Rises {
+                sunrise: got_rise,
+                sunset: got_set,
+            } => {
+                assert!(
+                    (got_rise - sunrise).abs() <= 2,
+                    "sunrise {} != {}",
+                    got_rise,
+                    sunrise
+                );
+                assert!(
+                    (got_set - sunset).abs() <= 2,
+                    "sunset {} != {}",
+                    got_set,
+                    sunset
+                );
+            }
+            other => panic!("sun doesn't rise and set: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn london() {
+        let london = Location::new(515, -1);
+        assert_rises(
+            sun_times(&on(26, 6, 21), &london),
+            3 * 60 + 42,
+            20 * 60 + 21,
+        );
+        assert_rises(
+            sun_times(&on(26, 12, 21), &london),
+            8 * 60 + 3,
+            15 * 60 + 53,
+        );
+    }
+
+    #[test]
+    fn equator() {
+        let null_island = Location::new(0, 0);
+        assert_rises(
+            sun_times(&on(26, 9, 23), &null_island),
+            5 * 60 + 49,
+            17 * 60 + 56,
+        );
+    }
+
+    #[test]
+    fn far_from_greenwich() {
+        // Singapore's sunrise is before midnight UTC.
+        let singapore = Location::new(14, 1038);
+        assert_rises(sun_times(&on(26, 3, 20), &singapore), -50, 11 * 60 + 16);
+        // In the southern hemisphere, December has the longest days.
+        let sydney = Location::new(-339, 1512);
+        assert_rises(sun_times(&on(26, 12, 21), &sydney), -319, 9 * 60 + 5);
+    }
+
+    #[test]
+    fn polar_day_and_night() {
+        let tromso = Location::new(696, 189);
+        assert_eq!(sun_times(&on(26, 6, 21), &tromso), SunTimes::AlwaysUp);
+        assert_eq!(sun_times(&on(26, 12, 21), &tromso), SunTimes::AlwaysDown);
+        // At the poles themselves the calculation takes a different path.
+        let south_pole = Location::new(-900, 0);
+        assert_eq!(sun_times(&on(26, 12, 21), &south_pole), SunTimes::AlwaysUp);
+        assert_eq!(sun_times(&on(26, 6, 21), &south_pole), SunTimes::AlwaysDown);
+    }
+}